@@ -9,9 +9,41 @@ pub struct Model {
     pub id: i32,
     pub data: String,
     pub added_at: DateTime,
+    pub pinned: bool,
+    pub deleted_at: Option<DateTime>,
+    pub kinds: Option<String>,
+    pub language: Option<String>,
+    pub source_app: Option<String>,
+    pub use_count: i32,
+    pub last_used_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::entry_format::Entity")]
+    EntryFormat,
+    #[sea_orm(has_many = "super::entry_revision::Entity")]
+    EntryRevision,
+    #[sea_orm(has_many = "super::entry_tag::Entity")]
+    EntryTag,
+}
+
+impl Related<super::entry_format::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EntryFormat.def()
+    }
+}
+
+impl Related<super::entry_revision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EntryRevision.def()
+    }
+}
+
+impl Related<super::entry_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EntryTag.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
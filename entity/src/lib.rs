@@ -3,3 +3,6 @@
 pub mod prelude;
 
 pub mod entry;
+pub mod entry_format;
+pub mod entry_revision;
+pub mod entry_tag;
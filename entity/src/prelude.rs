@@ -1,3 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
 pub use super::entry::Entity as Entry;
+pub use super::entry_format::Entity as EntryFormat;
+pub use super::entry_revision::Entity as EntryRevision;
+pub use super::entry_tag::Entity as EntryTag;
@@ -6,4 +6,18 @@ pub enum I {
     Entry,
     Data,
     AddedAt,
+    EntryFormat,
+    EntryId,
+    Format,
+    Pinned,
+    EntryTag,
+    Tag,
+    DeletedAt,
+    EntryRevision,
+    EditedAt,
+    Kinds,
+    Language,
+    SourceApp,
+    UseCount,
+    LastUsedAt,
 }
@@ -2,12 +2,28 @@ pub use sea_orm_migration::prelude::*;
 
 mod idents;
 mod m20220101_000001_create_table;
+mod m20241220_000002_create_entry_format_table;
+mod m20241221_000003_add_pin_and_tags;
+mod m20241222_000004_add_entry_deleted_at;
+mod m20241223_000005_create_entry_revision_table;
+mod m20241224_000006_add_entry_kinds;
+mod m20241225_000007_add_entry_usage;
+mod m20241226_000008_add_entry_last_used_at;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20241220_000002_create_entry_format_table::Migration),
+            Box::new(m20241221_000003_add_pin_and_tags::Migration),
+            Box::new(m20241222_000004_add_entry_deleted_at::Migration),
+            Box::new(m20241223_000005_create_entry_revision_table::Migration),
+            Box::new(m20241224_000006_add_entry_kinds::Migration),
+            Box::new(m20241225_000007_add_entry_usage::Migration),
+            Box::new(m20241226_000008_add_entry_last_used_at::Migration),
+        ]
     }
 }
@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::idents::I;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(I::EntryFormat)
+                    .col(pk_auto(I::Id))
+                    .col(integer(I::EntryId))
+                    .col(string(I::Format))
+                    .col(string(I::Data))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(I::EntryFormat, I::EntryId)
+                            .to(I::Entry, I::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(I::EntryFormat).to_owned())
+            .await
+    }
+}
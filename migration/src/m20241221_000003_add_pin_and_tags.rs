@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::idents::I;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(I::Entry)
+                    .add_column(boolean(I::Pinned).default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(I::EntryTag)
+                    .col(pk_auto(I::Id))
+                    .col(integer(I::EntryId))
+                    .col(string(I::Tag))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(I::EntryTag, I::EntryId)
+                            .to(I::Entry, I::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(I::EntryTag).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(I::Entry)
+                    .drop_column(I::Pinned)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::idents::I;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(I::EntryRevision)
+                    .col(pk_auto(I::Id))
+                    .col(integer(I::EntryId))
+                    .col(string(I::Data))
+                    .col(date_time(I::EditedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(I::EntryRevision, I::EntryId)
+                            .to(I::Entry, I::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(I::EntryRevision).to_owned())
+            .await
+    }
+}
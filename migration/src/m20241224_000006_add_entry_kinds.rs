@@ -0,0 +1,49 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::idents::I;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Sqlite only alters one column at a time. Both stay null until the entry is
+        // classified, existing entries are classified by the app on startup.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(I::Entry)
+                    .add_column(string_null(I::Kinds))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(I::Entry)
+                    .add_column(string_null(I::Language))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(I::Entry)
+                    .drop_column(I::Language)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(I::Entry)
+                    .drop_column(I::Kinds)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -1,6 +1,13 @@
-use std::{collections::HashMap, fmt::Debug, thread, time::Duration};
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Debug,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
 
-use clipboard_rs::{Clipboard, ClipboardContext};
+use chrono::{Local, NaiveDateTime};
 use iced::{
     advanced::graphics::image::image_rs::load_from_memory,
     event::{self, Status},
@@ -19,17 +26,39 @@ use sea_orm::DatabaseConnection;
 use tokio::{sync::mpsc, time::sleep};
 
 use crate::{
-    clipboard::ClipboardListener,
-    db::{get_db, repo},
-    tray::subscribe_tray_menu_event,
+    classify::{self, ContentKind},
+    clipboard::{
+        self, ClipboardBackend, ClipboardContent, ClipboardFormat, ClipboardListener, SelfWrites,
+        SystemClipboard,
+    },
+    config::{self, Config, RepasteBehavior},
+    db::{
+        get_db,
+        repo::{self, SyncOutcome},
+        Item,
+    },
+    external_editor,
+    hooks::CapturedEntry,
+    hotkey,
+    input::{self, InputBackend, RdevInput},
+    paste::{self, ActiveWindow, PasteAction, YankPopReplace},
+    plugin::{PluginClassifier, PluginHost},
+    scripting::{CaptureHooks, ScriptEntry, Scripts},
+    smart_action::{ActionOutput, SmartAction},
+    tray::{subscribe_tray_menu_event, Tray},
     utils::{self, iced_event_to_shortcut, ASYNC_CHANNEL_SIZE},
-    window::{self, Window},
+    window::{
+        self,
+        history::{BulkAction, PaletteAction, PasteMode},
+        Window,
+    },
     JOY_CLIPPY_ICON,
 };
 
 const DEFAULT_TOGGLE_MODIFIERS: iced::keyboard::Modifiers = Modifiers::ALT;
 const DEFAULT_TOGGLE_PHYSICAL_KEY: iced::keyboard::key::Physical = Physical::Code(Code::F9);
 const DEFAULT_TOGGLE_LOGICAL_KEY: iced::keyboard::Key = Key::Named(key::Named::F9);
+const YANK_POP_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone)]
 pub struct Shortcut {
@@ -39,8 +68,120 @@ pub struct Shortcut {
     pub rdev_key: rdev::Key,
 }
 
+/// Copy along with what capture scripts and classifier plugins made of it
+#[derive(Debug, Clone)]
+pub struct Capture {
+    text: String,
+    contents: Vec<ClipboardContent>,
+    tags: Vec<String>,
+    /// Kinds the text is classified as, for capture hook filters
+    kinds: Vec<ContentKind>,
+    /// Class of the window focused when the copy was made, empty when unknown
+    source_app: String,
+    copied_at: NaiveDateTime,
+}
+
+impl Capture {
+    /// Runs the capture scripts then the classifier plugins, `None` when a script vetoed the copy
+    fn process(
+        text: String,
+        contents: Vec<ClipboardContent>,
+        copied_at: NaiveDateTime,
+        capture_hooks: &CaptureHooks,
+        classifiers: &[PluginClassifier],
+    ) -> Option<Capture> {
+        let source_app = paste::active_window()
+            .map(|window| window.class)
+            .unwrap_or_default();
+        let (text, mut tags) = if capture_hooks.is_empty() {
+            (text, Vec::new())
+        } else {
+            let entry = ScriptEntry {
+                text,
+                formats: contents
+                    .iter()
+                    .map(|content| format!("{:?}", content.format()).to_lowercase())
+                    .collect(),
+                tags: Vec::new(),
+                source_app: source_app.clone(),
+            };
+            let entry = capture_hooks.run(entry)?;
+            (entry.text, entry.tags)
+        };
+        tags.extend(
+            classifiers
+                .iter()
+                .filter_map(|classifier| {
+                    classifier
+                        .classify(&text)
+                        .inspect_err(|e| {
+                            tracing::error!(
+                                "Plugin {} failed to classify\n{e:?}",
+                                classifier.plugin
+                            )
+                        })
+                        .ok()
+                })
+                .filter(|class| !class.is_empty()),
+        );
+        let kinds = classify::classify(&text).kinds;
+        Some(Capture {
+            text,
+            contents,
+            tags,
+            kinds,
+            source_app,
+            copied_at,
+        })
+    }
+}
+
+/// Collect mode, copies are appended to a single entry one write at a time so none is lost
+#[derive(Debug, Default)]
+struct Collecting {
+    /// Known once the first copy is written
+    entry_id: Option<i32>,
+    writing: bool,
+    /// Copies made while a write was in flight, appended once it is done
+    queued: Vec<String>,
+}
+
 pub struct App {
-    clipboard_context: DebugImplIgnore<ClipboardContext>,
+    clipboard: DebugImplIgnore<Box<dyn ClipboardBackend>>,
+    input: Arc<dyn InputBackend>,
+    config: Config,
+    /// Window focused before the history window was opened, where the paste will land
+    paste_target: Option<ActiveWindow>,
+    /// Clipboard content to put back once the current paste is done
+    clipboard_snapshot: Option<Vec<ClipboardContent>>,
+    self_writes: SelfWrites,
+    /// Modifiers currently held system wide
+    global_modifiers: Modifiers,
+    /// Dispatched once the shortcut modifiers are released, simulated keys would combine with them
+    deferred_message: Option<Message>,
+    paste_queue: VecDeque<Item>,
+    /// Key to send right after the current paste
+    paste_suffix: Option<rdev::Key>,
+    /// Set while collect mode is on
+    collecting: Option<Collecting>,
+    /// Sequence number given to the next copy, copies are processed in parallel but stored
+    /// in the order they were made
+    next_capture: u64,
+    /// Sequence number of the next capture to store
+    next_stored_capture: u64,
+    /// Processed copies waiting for an earlier one, `None` when a script vetoed it
+    pending_captures: BTreeMap<u64, Option<Capture>>,
+    tray: Option<Tray>,
+    scripts: Scripts,
+    plugins: PluginHost,
+    /// Incremented on each plugin reload so only the last one started is kept
+    plugins_generation: u64,
+    /// Off when the config file could not be loaded, saving would overwrite it with defaults
+    config_saves_enabled: bool,
+    /// Set while the config is written, saves are done one at a time so the last one wins
+    saving_config: bool,
+    /// Latest config changed while a save was in flight, written once it is done
+    queued_config: Option<Config>,
     windows: HashMap<iced::window::Id, Window>,
     db: DatabaseConnection,
     toggle_shortcut: Shortcut,
@@ -53,6 +194,8 @@ pub enum Message {
 
     // Window general
     RequestWindowClose(iced::window::Id),
+    /// Opens a window with the message, for failures the user has to know about
+    ShowError(String),
     WindowClose(iced::window::Id),
     LooseFocus(iced::window::Id),
     Panic(String),
@@ -60,34 +203,138 @@ pub enum Message {
 
     // Clipboard
     ClipboardEvent,
-    RequestPaste(entity::entry::Model),
-    SetClipboardItem(entity::entry::Model),
+    /// Copy with its sequence number, once capture scripts and plugins ran on it.
+    /// `None` when it was vetoed or could not be processed.
+    Captured(u64, Option<Capture>),
+    RequestPaste(Item, PasteMode),
+    SetClipboardItem(Item, PasteMode),
     SimulatePaste,
+    QuickPaste(Option<Item>),
+    YankPop,
+    YankPopItem(u64, Option<Item>),
+    YankPopPaste(Item),
+    YankPopTimeout(u64),
+    /// Replaces the given number of just pasted characters with the item
+    ReplacePaste(Item, usize),
+    SetPasteQueue(Vec<Item>),
+    PasteNextQueued,
+    PasteQueued(Item),
+    ToggleCollectMode,
+    /// Id of the collecting entry once a write is done, `None` when it failed
+    CollectEntryUpdated(Option<i32>),
+    /// Runs the matching capture hooks on a newly added entry
+    RunCaptureHooks(CapturedEntry),
+    BulkAction(BulkAction),
+    /// Pastes the text of the items joined with the configured separator
+    PasteJoined(Vec<Item>),
+    /// Runs the pipeline at the given index on the clipboard text, replacing it
+    TransformClipboard(usize),
+    PasteDone,
+    RestoreClipboard(Vec<ClipboardContent>),
+    TypeOut(String),
 
     // History window
     RequestOpenHistoryWindow,
     RequestCloseHistoryWindow,
-    HistoryWindowLoaded(iced::window::Id, Vec<entity::entry::Model>),
+    HistoryWindowLoaded(iced::window::Id, Vec<Item>),
+    RefreshHistoryWindow,
+    /// Pastes the text as is, without storing it in the history
+    PasteText(String),
+    /// Stores the text as a new history entry
+    AddEntry(String),
+    /// Pastes the text produced by the action, or closes the history once it launched an app
+    RunSmartAction(String, SmartAction),
+    UndoToastTimeout(u64),
+    PluginPreviewRendered(i32, String),
     HistoryWindowEvent(iced::window::Id, window::history::Message),
 
     // Settings window
     OpenSettingsWindow,
     SettingsWindowEvent(iced::window::Id, window::settings::Message),
+    ReloadScripts,
+    ReloadPlugins,
+    PluginsLoaded(u64, PluginHost),
+
+    // Editor window
+    OpenEditorWindow(Item),
+    CloseEditorWindow,
+    EditorWindowEvent(iced::window::Id, window::editor::Message),
+    SaveEntryEdit(i32, String),
+    ExternalEdit(Item),
+
+    // Trash window
+    OpenTrashWindow,
+    RefreshTrashWindow,
+    TrashWindowEvent(iced::window::Id, window::trash::Message),
+    RestoreItems(Vec<i32>),
+    EmptyTrash,
+    /// Permanently removes entries trashed for longer than the retention period
+    PurgeTrash,
 
     // Async events
     DbConnection(DatabaseConnection),
 
     // Business
     UpdateToggleShortcut(Shortcut),
+    UpdateConfig(Config),
+    ConfigSaved,
 }
 
 impl App {
-    pub fn new() -> (Self, Task<Message>) {
+    pub fn new(tray: Tray) -> (Self, Task<Message>) {
+        let (config, load_error) = match config::load() {
+            Ok(config) => (config, None),
+            Err(e) => {
+                tracing::error!("Could not load config, falling back to defaults\n{e:?}");
+                (Config::default(), Some(e))
+            }
+        };
+        let input = Self::create_input_backend(&config);
+        let (mut app, mut task) = Self::with_backends(
+            Box::new(SystemClipboard::new().expect("Retrieval of system clipboard")),
+            input,
+            config,
+        );
+        app.tray = Some(tray);
+        app.scripts = Scripts::load();
+        if let Some(e) = load_error {
+            app.config_saves_enabled = false;
+            task = task.chain(Task::done(Message::ShowError(format!(
+                "Could not load the config, defaults are used and changes will not be saved \
+                 until it is fixed:\n{e}"
+            ))));
+        }
+        (app, Task::batch([task, Task::done(Message::ReloadPlugins)]))
+    }
+
+    pub fn with_backends(
+        clipboard: Box<dyn ClipboardBackend>,
+        input: Arc<dyn InputBackend>,
+        config: Config,
+    ) -> (Self, Task<Message>) {
         (
             Self {
-                clipboard_context: ClipboardContext::new()
-                    .expect("Retrieval of system clipboard")
-                    .into(),
+                clipboard: clipboard.into(),
+                input,
+                config,
+                paste_target: None,
+                clipboard_snapshot: None,
+                self_writes: Default::default(),
+                global_modifiers: Modifiers::empty(),
+                deferred_message: None,
+                paste_queue: Default::default(),
+                paste_suffix: None,
+                collecting: None,
+                next_capture: 0,
+                next_stored_capture: 0,
+                pending_captures: BTreeMap::new(),
+                tray: None,
+                scripts: Scripts::default(),
+                plugins: PluginHost::default(),
+                plugins_generation: 0,
+                config_saves_enabled: true,
+                saving_config: false,
+                queued_config: None,
                 windows: Default::default(),
                 db: DatabaseConnection::Disconnected,
                 toggle_shortcut: Shortcut {
@@ -106,6 +353,16 @@ impl App {
         )
     }
 
+    fn create_input_backend(config: &Config) -> Arc<dyn InputBackend> {
+        config.input_backend.create().unwrap_or_else(|e| {
+            tracing::error!(
+                "Could not create {} input backend, falling back to rdev\n{e:?}",
+                config.input_backend
+            );
+            Arc::new(RdevInput)
+        })
+    }
+
     fn get_icon() -> iced::window::Icon {
         let icon_data = load_from_memory(JOY_CLIPPY_ICON).expect("Icon loading");
         let (width, height) = (icon_data.width(), icon_data.height());
@@ -121,18 +378,28 @@ impl App {
                     Some(Window::History(window::history::State::Loading))
                 ) {
                     if let Some(Window::History(state)) = self.windows.get_mut(&id) {
-                        *state = window::history::State::Loaded {
-                            selected_item_cursor: 0,
+                        let (loaded, task) = window::history::State::loaded(
                             items,
-                        }
+                            self.paste_queue.iter().map(|item| item.entry.id).collect(),
+                            self.plugins.previewers(),
+                            self.config.preview_pane,
+                        );
+                        *state = loaded;
+                        return task;
                     }
                 }
 
                 Task::none()
             }
             Message::OpenSettingsWindow => {
+                // Pipelines are previewed against the entry selected in the history window
+                let preview_text = self
+                    .get_history_window()
+                    .and_then(window::history::State::cursor_item)
+                    .map(|item| item.entry.data.clone())
+                    .unwrap_or_default();
                 let (id, open_task) = iced::window::open(Settings {
-                    size: Size::new(500., 300.),
+                    size: Size::new(600., 450.),
                     resizable: true,
                     icon: Some(Self::get_icon()),
                     ..Default::default()
@@ -140,12 +407,208 @@ impl App {
 
                 self.windows.insert(
                     id,
-                    Window::Settings(window::settings::State::new(self.toggle_shortcut.clone())),
+                    Window::Settings(window::settings::State::new(
+                        self.toggle_shortcut.clone(),
+                        self.config.clone(),
+                        preview_text,
+                        self.scripts.statuses(),
+                        self.plugins.statuses(),
+                    )),
                 );
 
                 open_task.chain(iced::window::gain_focus(id)).discard()
             }
+            Message::OpenEditorWindow(item) => {
+                let entry_id = item.entry.id;
+                let (id, open_task) = match self.get_editor_window_id() {
+                    Some(id) => (id, iced::window::gain_focus(id)),
+                    None => {
+                        let (id, open_task) = iced::window::open(Settings {
+                            size: Size::new(500., 400.),
+                            resizable: true,
+                            icon: Some(Self::get_icon()),
+                            ..Default::default()
+                        });
+                        (id, open_task.chain(iced::window::gain_focus(id)).discard())
+                    }
+                };
+                self.windows
+                    .insert(id, Window::Editor(window::editor::State::new(item)));
+
+                let db = self.db.clone();
+                open_task.chain(
+                    Task::future(async move {
+                        repo::get_revisions(&db, entry_id)
+                            .await
+                            .inspect_err(|e| tracing::error!("Could not retrieve revisions\n{e:?}"))
+                            .ok()
+                    })
+                    .and_then(move |revisions| {
+                        Task::done(Message::EditorWindowEvent(
+                            id,
+                            window::editor::Message::RevisionsLoaded(
+                                revisions
+                                    .into_iter()
+                                    .map(window::editor::Revision)
+                                    .collect(),
+                            ),
+                        ))
+                    }),
+                )
+            }
+            Message::CloseEditorWindow => match self.get_editor_window_id() {
+                Some(id) => iced::window::close(id),
+                None => Task::none(),
+            },
+            Message::EditorWindowEvent(window_id, message) => {
+                if let Some(Window::Editor(state)) = self.windows.get_mut(&window_id) {
+                    state.update(message)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::SaveEntryEdit(entry_id, text) => {
+                for item in self
+                    .paste_queue
+                    .iter_mut()
+                    .filter(|item| item.entry.id == entry_id)
+                {
+                    item.entry.data = text.clone();
+                    item.formats.clear();
+                }
+                let db = self.db.clone();
+                Task::future(async move {
+                    if let Err(e) = repo::edit_item(&db, entry_id, text).await {
+                        tracing::error!("Could not save entry\n{e:?}");
+                    }
+                })
+                .discard()
+                .chain(Task::done(Message::RefreshHistoryWindow))
+            }
+            Message::ExternalEdit(item) => {
+                let db = self.db.clone();
+                Task::future(async move {
+                    let original = item.entry.data;
+                    let result = match external_editor::edit(item.entry.id, &original).await {
+                        Ok(edited) => repo::sync_edit(&db, item.entry.id, &original, edited).await,
+                        Err(e) => Err(e),
+                    };
+                    match result {
+                        Ok(SyncOutcome::AddedAsNew) => {
+                            tracing::warn!(
+                                "Entry changed while being edited, the edit was saved as a new entry"
+                            );
+                            None
+                        }
+                        Ok(_) => None,
+                        Err(e) => {
+                            tracing::error!("Could not edit entry externally\n{e:?}");
+                            Some(Message::ShowError(format!(
+                                "Could not edit entry externally\n{e:#}"
+                            )))
+                        }
+                    }
+                })
+                .then(|error| {
+                    error
+                        .map_or(Task::none(), Task::done)
+                        .chain(Task::done(Message::RefreshHistoryWindow))
+                })
+            }
+            Message::OpenTrashWindow => {
+                if let Some(id) = self.get_trash_window_id() {
+                    return iced::window::gain_focus(id);
+                }
+                let (id, open_task) = iced::window::open(Settings {
+                    size: Size::new(500., 450.),
+                    resizable: true,
+                    icon: Some(Self::get_icon()),
+                    ..Default::default()
+                });
+
+                self.windows
+                    .insert(id, Window::Trash(window::trash::State::Loading));
+
+                open_task
+                    .chain(iced::window::gain_focus(id))
+                    .discard()
+                    .chain(Task::done(Message::RefreshTrashWindow))
+            }
+            Message::RefreshTrashWindow => {
+                let Some(id) = self.get_trash_window_id() else {
+                    return Task::none();
+                };
+                let db = self.db.clone();
+                Task::future(async move {
+                    repo::get_trashed_items(&db)
+                        .await
+                        .inspect_err(|e| tracing::error!("Could not retrieve trash\n{e:?}"))
+                        .ok()
+                })
+                .and_then(move |items| {
+                    Task::done(Message::TrashWindowEvent(
+                        id,
+                        window::trash::Message::Loaded(items),
+                    ))
+                })
+            }
+            Message::TrashWindowEvent(window_id, message) => {
+                if let Some(Window::Trash(state)) = self.windows.get_mut(&window_id) {
+                    state.update(message)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::RestoreItems(ids) => {
+                let db = self.db.clone();
+                Task::future(async move {
+                    if let Err(e) = repo::restore_items(&db, &ids).await {
+                        tracing::error!("Could not restore entries\n{e:?}");
+                    }
+                })
+                .discard()
+                .chain(Task::batch([
+                    Task::done(Message::RefreshHistoryWindow),
+                    Task::done(Message::RefreshTrashWindow),
+                ]))
+            }
+            Message::EmptyTrash => {
+                let db = self.db.clone();
+                Task::future(async move {
+                    if let Err(e) = repo::purge_trash(&db, None).await {
+                        tracing::error!("Could not empty trash\n{e:?}");
+                    }
+                })
+                .discard()
+                .chain(Task::done(Message::RefreshTrashWindow))
+            }
+            Message::PurgeTrash => {
+                if self.config.trash_retention_days == 0 {
+                    return Task::none();
+                }
+                let deleted_before = Local::now().naive_local()
+                    - chrono::Duration::days(self.config.trash_retention_days as i64);
+                let db = self.db.clone();
+                Task::future(async move {
+                    if let Err(e) = repo::purge_trash(&db, Some(deleted_before)).await {
+                        tracing::error!("Could not purge trash\n{e:?}");
+                    }
+                })
+                .discard()
+            }
             Message::RequestWindowClose(id) => iced::window::close(id),
+            Message::ShowError(message) => {
+                let (id, open_task) = iced::window::open(Settings {
+                    level: Level::AlwaysOnTop,
+                    position: Position::Centered,
+                    size: Size::new(400., 150.),
+                    icon: Some(Self::get_icon()),
+                    ..Default::default()
+                });
+                self.windows
+                    .insert(id, Window::Error(window::error::State::new(message)));
+                open_task.chain(iced::window::gain_focus(id)).discard()
+            }
             Message::WindowClose(id) => {
                 self.windows.remove(&id);
                 Task::none()
@@ -159,38 +622,178 @@ impl App {
             }
             Message::ExitApp => iced::exit(),
             Message::ClipboardEvent => {
-                let db = self.db.clone();
-                if let Ok(text) = self.clipboard_context.get_text() {
-                    Task::future(async move { crate::db::repo::add_item(&db, text).await })
-                        .discard()
-                } else {
-                    Task::none()
+                let Ok(contents) = self.clipboard.read_all() else {
+                    return Task::none();
+                };
+                if self.self_writes.is_self_write(&contents) {
+                    return Task::none();
+                }
+                let Some(text) = clipboard::find_text(&contents).map(str::to_owned) else {
+                    return Task::none();
+                };
+                let copied_at = Local::now().naive_local();
+                let sequence = self.next_capture;
+                self.next_capture += 1;
+                let capture_hooks = self.scripts.capture_hooks();
+                let classifiers = self.plugins.classifiers();
+                // Scripts, plugins and the active window lookup all block
+                Task::perform(
+                    tokio::task::spawn_blocking(move || {
+                        Capture::process(text, contents, copied_at, &capture_hooks, &classifiers)
+                    }),
+                    move |capture| {
+                        let capture = capture
+                            .inspect_err(|e| tracing::error!("Could not process copy\n{e:?}"))
+                            .ok()
+                            .flatten();
+                        Message::Captured(sequence, capture)
+                    },
+                )
+            }
+            Message::Captured(sequence, capture) => {
+                self.pending_captures.insert(sequence, capture);
+                let mut tasks = Vec::new();
+                while let Some(capture) = self.pending_captures.remove(&self.next_stored_capture) {
+                    self.next_stored_capture += 1;
+                    if let Some(capture) = capture {
+                        tasks.push(self.store_capture(capture));
+                    }
                 }
+                Task::batch(tasks)
+            }
+            Message::RunCaptureHooks(entry) => {
+                let hooks = self
+                    .config
+                    .capture_hooks
+                    .iter()
+                    .filter(|hook| hook.matches(&entry))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                Task::batch(hooks.into_iter().map(|hook| {
+                    let db = self.db.clone();
+                    let entry = entry.clone();
+                    // Runs in the background, slow commands never hold back new captures
+                    Task::future(async move {
+                        let output = hook
+                            .run(&entry)
+                            .await
+                            .inspect_err(|e| {
+                                tracing::error!("Capture hook {} failed\n{e:?}", hook.command)
+                            })
+                            .ok()?;
+                        if !hook.replace_entry || output.is_empty() || output == entry.text {
+                            return None;
+                        }
+                        repo::replace_with_hook_output(&db, entry.entry_id, output)
+                            .await
+                            .inspect_err(|e| {
+                                tracing::error!("Could not replace entry with hook output\n{e:?}")
+                            })
+                            .ok()
+                    })
+                    .and_then(|_| Task::done(Message::RefreshHistoryWindow))
+                }))
+            }
+            Message::RequestPaste(item, PasteMode::TypeOut) => {
+                let db = self.db.clone();
+                let id = item.entry.id;
+                let record_use = Task::future(async move {
+                    if let Err(e) = repo::record_use(&db, id).await {
+                        tracing::error!("Could not record entry use\n{e:?}");
+                    }
+                })
+                .discard();
+                // Typed keys would combine with the modifiers of the shortcut still held
+                let type_out = self.when_modifiers_released(Message::TypeOut(item.entry.data));
+                Task::done(Message::RequestCloseHistoryWindow)
+                    .chain(type_out)
+                    .chain(record_use)
+            }
+            Message::RequestPaste(item, mode) => {
+                self.snapshot_clipboard();
+                Task::done(Message::RequestCloseHistoryWindow)
+                    .chain(Task::done(Message::SetClipboardItem(item, mode)))
+                    .chain(Task::done(Message::SimulatePaste))
             }
-            Message::RequestPaste(item) => Task::done(Message::RequestCloseHistoryWindow)
-                .chain(Task::done(Message::SetClipboardItem(item)))
-                .chain(Task::done(Message::SimulatePaste)),
-            Message::SetClipboardItem(item) => {
-                self.clipboard_context
-                    .set_text(item.data.clone())
+            Message::SetClipboardItem(item, mode) => {
+                let contents = item.contents(matches!(mode, PasteMode::PlainText));
+                self.self_writes.record(&contents);
+                self.clipboard
+                    .write(contents)
                     .expect("Setting system clipboard value");
                 let db = self.db.clone();
-                Task::future(async move { repo::delete(&db, &item).await }).discard()
+                let bump = self.config.repaste_behavior == RepasteBehavior::Bump;
+                Task::future(async move {
+                    repo::record_use(&db, item.entry.id).await?;
+                    if bump {
+                        repo::bump(&db, &item.entry).await?;
+                    }
+                    anyhow::Ok(())
+                })
+                .then(|result| {
+                    if let Err(e) = result {
+                        tracing::error!("Could not record entry use\n{e:?}");
+                    }
+                    Task::none()
+                })
             }
-            Message::SimulatePaste => Task::future(async {
-                async fn simulate(event: rdev::EventType) {
-                    sleep(Duration::from_millis(20)).await;
-                    rdev::simulate(&event).unwrap();
-                    sleep(Duration::from_millis(20)).await;
-                    sleep(Duration::from_millis(20)).await;
+            Message::SimulatePaste => {
+                let target = self.paste_target.take().or_else(paste::active_window);
+                match paste::resolve_action(&self.config, target.as_ref()) {
+                    PasteAction::Chord(chord) => {
+                        let input = self.input.clone();
+                        let suffix = self.paste_suffix.take();
+                        Task::perform(
+                            async move {
+                                let mut result = input::tap_chord(input.as_ref(), &chord).await;
+                                if let (Ok(()), Some(suffix)) = (&result, suffix) {
+                                    result = input::tap_chord(input.as_ref(), &[suffix]).await;
+                                }
+                                if let Err(e) = result {
+                                    tracing::error!("Could not simulate paste\n{e:?}");
+                                }
+                            },
+                            |_| Message::PasteDone,
+                        )
+                    }
+                    PasteAction::CopyOnly => {
+                        // Nothing gets pasted, the item is meant to stay in the clipboard
+                        self.clipboard_snapshot = None;
+                        self.paste_suffix = None;
+                        Task::none()
+                    }
                 }
-
-                simulate(rdev::EventType::KeyPress(rdev::Key::ControlLeft)).await;
-                simulate(rdev::EventType::KeyPress(rdev::Key::KeyV)).await;
-                simulate(rdev::EventType::KeyRelease(rdev::Key::KeyV)).await;
-                simulate(rdev::EventType::KeyRelease(rdev::Key::ControlLeft)).await;
-            })
-            .discard(),
+            }
+            Message::PasteDone => match self.clipboard_snapshot.take() {
+                Some(snapshot) => {
+                    let delay = Duration::from_millis(self.config.restore_clipboard_delay_ms);
+                    Task::perform(sleep(delay), move |_| {
+                        Message::RestoreClipboard(snapshot.clone())
+                    })
+                }
+                None => Task::none(),
+            },
+            Message::RestoreClipboard(contents) => {
+                if contents.is_empty() {
+                    return Task::none();
+                }
+                self.self_writes.record(&contents);
+                if let Err(e) = self.clipboard.write(contents) {
+                    tracing::error!("Could not restore clipboard\n{e:?}");
+                }
+                Task::none()
+            }
+            Message::TypeOut(text) => {
+                let input = self.input.clone();
+                let layout = self.config.keyboard_layout;
+                let delay = Duration::from_millis(self.config.type_out_delay_ms);
+                Task::future(async move {
+                    if let Err(e) = input::type_text(input.as_ref(), &text, layout, delay).await {
+                        tracing::error!("Could not type out entry\n{e:?}");
+                    }
+                })
+                .discard()
+            }
             Message::HistoryWindowEvent(window_id, message) => {
                 if let Some(Window::History(state)) = self.windows.get_mut(&window_id) {
                     state.update(message)
@@ -198,6 +801,45 @@ impl App {
                     Task::none()
                 }
             }
+            Message::ReloadScripts => {
+                self.scripts = Scripts::load();
+                match self.get_settings_window_id() {
+                    Some(id) => Task::done(Message::SettingsWindowEvent(
+                        id,
+                        window::settings::Message::ScriptsReloaded(self.scripts.statuses()),
+                    )),
+                    None => Task::none(),
+                }
+            }
+            Message::ReloadPlugins => {
+                self.plugins_generation += 1;
+                let generation = self.plugins_generation;
+                let settings = self.config.plugins.clone();
+                // Compiling the modules takes a while
+                Task::future(tokio::task::spawn_blocking(move || {
+                    PluginHost::load(&settings)
+                }))
+                .then(move |plugins| match plugins {
+                    Ok(plugins) => Task::done(Message::PluginsLoaded(generation, plugins)),
+                    Err(e) => {
+                        tracing::error!("Could not load plugins\n{e:?}");
+                        Task::none()
+                    }
+                })
+            }
+            Message::PluginsLoaded(generation, plugins) => {
+                if generation != self.plugins_generation {
+                    return Task::none();
+                }
+                self.plugins = plugins;
+                match self.get_settings_window_id() {
+                    Some(id) => Task::done(Message::SettingsWindowEvent(
+                        id,
+                        window::settings::Message::PluginsReloaded(self.plugins.statuses()),
+                    )),
+                    None => Task::none(),
+                }
+            }
             Message::SettingsWindowEvent(window_id, message) => {
                 if let Some(Window::Settings(state)) = self.windows.get_mut(&window_id) {
                     state.update(message)
@@ -213,15 +855,25 @@ impl App {
                 }
             }
             Message::DbConnection(db) => {
-                self.db = db;
-                Task::none()
+                self.db = db.clone();
+                Task::future(async move {
+                    if let Err(e) = repo::classify_entries(&db).await {
+                        tracing::error!("Could not classify entries\n{e:?}");
+                    }
+                })
+                .then(|_| Task::done(Message::RefreshHistoryWindow))
             }
             Message::RequestOpenHistoryWindow => {
+                self.paste_target = paste::active_window();
                 let (id, open_task) = iced::window::open(Settings {
                     decorations: false,
                     level: Level::AlwaysOnTop,
                     position: Position::Centered,
-                    size: Size::new(200., 450.),
+                    size: if self.config.preview_pane {
+                        Size::new(600., 450.)
+                    } else {
+                        Size::new(200., 450.)
+                    },
                     exit_on_close_request: false,
                     icon: Some(Self::get_icon()),
                     ..Default::default()
@@ -242,12 +894,14 @@ impl App {
                             )
                         },
                     ))
+                    .chain(Task::done(Message::PurgeTrash))
             }
             Message::Panic(message) => {
                 tracing::error!("A fatal error occured\n{message}");
                 Task::done(Message::ExitApp)
             }
             Message::GlobalEvent(modifiers, event) => {
+                self.global_modifiers = modifiers;
                 let Shortcut {
                     modifiers: toggle_modifiers,
                     rdev_key,
@@ -256,10 +910,322 @@ impl App {
                 if matches!(event.event_type, rdev::EventType::KeyPress(key) if &key == rdev_key && toggle_modifiers == &modifiers)
                 {
                     Task::done(Message::RequestOpenHistoryWindow)
+                } else if let Some(index) = self.quick_paste_index(modifiers, &event) {
+                    self.fetch_item_at(index, Message::QuickPaste)
+                } else if self
+                    .config
+                    .yank_pop_shortcut
+                    .is_some_and(|shortcut| shortcut.matches(modifiers, &event))
+                {
+                    Task::done(Message::YankPop)
+                } else if self
+                    .config
+                    .queue_paste_shortcut
+                    .is_some_and(|shortcut| shortcut.matches(modifiers, &event))
+                {
+                    Task::done(Message::PasteNextQueued)
+                } else if self
+                    .config
+                    .collect_shortcut
+                    .is_some_and(|shortcut| shortcut.matches(modifiers, &event))
+                {
+                    Task::done(Message::ToggleCollectMode)
+                } else if let Some(index) = self.config.pipelines.iter().position(|pipeline| {
+                    pipeline
+                        .shortcut
+                        .is_some_and(|shortcut| shortcut.matches(modifiers, &event))
+                }) {
+                    Task::done(Message::TransformClipboard(index))
+                } else if modifiers.is_empty() && self.deferred_message.is_some() {
+                    Task::done(self.deferred_message.take().unwrap())
                 } else {
                     Task::none()
                 }
             }
+            Message::QuickPaste(item) => {
+                let Some(item) = item else {
+                    return Task::none();
+                };
+                self.paste_target = None;
+                self.when_modifiers_released(Message::RequestPaste(item, PasteMode::Formatted))
+            }
+            Message::YankPop => {
+                let index = self
+                    .get_yank_pop_window()
+                    .map_or(0, |(_, state)| state.index + 1);
+                self.fetch_item_at(index, move |item| Message::YankPopItem(index, item))
+            }
+            Message::YankPopItem(index, item) => {
+                let Some(item) = item else {
+                    // Cycled past the oldest entry, start over from the latest
+                    return if index > 0 {
+                        self.fetch_item_at(0, |item| Message::YankPopItem(0, item))
+                    } else {
+                        Task::none()
+                    };
+                };
+                let preview = item
+                    .entry
+                    .data
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_owned();
+                let (generation, open_task) = match self.get_yank_pop_window_mut() {
+                    Some(state) => {
+                        state.index = index;
+                        state.preview = preview;
+                        state.generation += 1;
+                        (state.generation, Task::none())
+                    }
+                    None => {
+                        let (id, open_task) = iced::window::open(Settings {
+                            decorations: false,
+                            level: Level::AlwaysOnTop,
+                            position: Position::Centered,
+                            size: Size::new(300., 60.),
+                            resizable: false,
+                            exit_on_close_request: false,
+                            // Keeps the focus on the window being pasted into
+                            #[cfg(target_os = "linux")]
+                            platform_specific: iced::window::settings::PlatformSpecific {
+                                override_redirect: true,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        });
+                        self.windows.insert(
+                            id,
+                            Window::YankPop(window::yank_pop::State::new(index, preview)),
+                        );
+                        (0, open_task.discard())
+                    }
+                };
+                self.paste_target = None;
+                Task::batch([
+                    open_task,
+                    self.when_modifiers_released(Message::YankPopPaste(item)),
+                    Task::perform(sleep(YANK_POP_TIMEOUT), move |_| {
+                        Message::YankPopTimeout(generation)
+                    }),
+                ])
+            }
+            Message::YankPopPaste(item) => {
+                let chars = item.entry.data.chars().count();
+                let pasted_chars = self
+                    .get_yank_pop_window_mut()
+                    .and_then(|state| state.pasted_chars.replace(chars));
+                match pasted_chars {
+                    Some(pasted_chars) => Task::done(Message::ReplacePaste(item, pasted_chars)),
+                    None => Task::done(Message::RequestPaste(item, PasteMode::Formatted)),
+                }
+            }
+            Message::YankPopTimeout(generation) => match self.get_yank_pop_window() {
+                Some((id, state)) if state.generation == generation => {
+                    Task::done(Message::RequestWindowClose(id))
+                }
+                _ => Task::none(),
+            },
+            Message::SetPasteQueue(items) => {
+                self.paste_queue = items.into();
+                self.update_tray();
+                Task::none()
+            }
+            Message::PasteNextQueued => {
+                let Some(item) = self.paste_queue.pop_front() else {
+                    return Task::none();
+                };
+                self.update_tray();
+                self.paste_target = None;
+                self.when_modifiers_released(Message::PasteQueued(item))
+            }
+            Message::ToggleCollectMode => {
+                self.collecting = match self.collecting {
+                    Some(_) => None,
+                    None => Some(Collecting::default()),
+                };
+                self.update_tray();
+                Task::none()
+            }
+            Message::CollectEntryUpdated(entry_id) => {
+                // Collect mode may have been turned off while the entry was written
+                let Some(collecting) = &mut self.collecting else {
+                    return Task::none();
+                };
+                collecting.writing = false;
+                if entry_id.is_some() {
+                    collecting.entry_id = entry_id;
+                }
+                if collecting.queued.is_empty() {
+                    return Task::none();
+                }
+                let queued = std::mem::take(&mut collecting.queued);
+                let text = queued.join(&self.config.collect_separator);
+                self.collect(text)
+            }
+            Message::BulkAction(action) => {
+                if let BulkAction::Delete(ids) | BulkAction::Merge(ids) = &action {
+                    self.paste_queue
+                        .retain(|item| !ids.contains(&item.entry.id));
+                    self.update_tray();
+                }
+                let db = self.db.clone();
+                let separator = self.config.join_separator.clone();
+                Task::future(async move {
+                    let result = match action {
+                        BulkAction::Delete(ids) => repo::delete_items(&db, &ids).await,
+                        BulkAction::SetPinned(ids, pinned) => {
+                            repo::set_pinned(&db, &ids, pinned).await
+                        }
+                        BulkAction::Tag(ids, tag) => repo::add_tag(&db, &ids, &tag).await,
+                        BulkAction::Merge(ids) => repo::merge_items(&db, &ids, &separator).await,
+                    };
+                    if let Err(e) = result {
+                        tracing::error!("Could not apply bulk action\n{e:?}");
+                    }
+                })
+                .discard()
+                .chain(Task::batch([
+                    Task::done(Message::RefreshHistoryWindow),
+                    Task::done(Message::RefreshTrashWindow),
+                ]))
+            }
+            Message::UndoToastTimeout(generation) => match self.get_history_window_id() {
+                Some(id) => Task::done(Message::HistoryWindowEvent(
+                    id,
+                    window::history::Message::UndoTimeout(generation),
+                )),
+                None => Task::none(),
+            },
+            Message::PluginPreviewRendered(entry_id, rendered) => {
+                match self.get_history_window_id() {
+                    Some(id) => Task::done(Message::HistoryWindowEvent(
+                        id,
+                        window::history::Message::PluginPreviewRendered(entry_id, rendered),
+                    )),
+                    None => Task::none(),
+                }
+            }
+            Message::PasteJoined(items) => {
+                let text = items
+                    .iter()
+                    .map(|item| item.entry.data.as_str())
+                    .collect::<Vec<_>>()
+                    .join(&self.config.join_separator);
+                Task::done(Message::PasteText(text))
+            }
+            Message::TransformClipboard(index) => {
+                let Some(pipeline) = self.config.pipelines.get(index) else {
+                    return Task::none();
+                };
+                let contents = match self.clipboard.read(&[ClipboardFormat::Text]) {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        tracing::error!("Could not read clipboard\n{e:?}");
+                        return Task::none();
+                    }
+                };
+                let Some(text) = clipboard::find_text(&contents) else {
+                    return Task::none();
+                };
+                match pipeline.apply(text) {
+                    Ok(transformed) => {
+                        // Html, Rtf and Files are other representations of the text before the
+                        // transformation, keeping them would paste the old content in apps
+                        // preferring them, so only the transformed text is left
+                        let contents = vec![ClipboardContent::Text(transformed)];
+                        self.self_writes.record(&contents);
+                        if let Err(e) = self.clipboard.write(contents) {
+                            tracing::error!("Could not set text to clipboard\n{e:?}");
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Could not run pipeline {}\n{e:?}", pipeline.name)
+                    }
+                }
+                Task::none()
+            }
+            Message::PasteText(text) => {
+                self.snapshot_clipboard();
+                let contents = vec![ClipboardContent::Text(text)];
+                self.self_writes.record(&contents);
+                if let Err(e) = self.clipboard.write(contents) {
+                    tracing::error!("Could not set text to clipboard\n{e:?}");
+                    return Task::none();
+                }
+                Task::done(Message::RequestCloseHistoryWindow)
+                    .chain(Task::done(Message::SimulatePaste))
+            }
+            Message::AddEntry(text) => {
+                let db = self.db.clone();
+                Task::future(async move {
+                    repo::add_item(
+                        &db,
+                        text,
+                        Vec::new(),
+                        Vec::new(),
+                        None,
+                        Local::now().naive_local(),
+                    )
+                    .await
+                    .inspect_err(|e| tracing::error!("Could not add entry\n{e:?}"))
+                    .ok()
+                })
+                .and_then(|_| Task::done(Message::RefreshHistoryWindow))
+            }
+            Message::RunSmartAction(text, action) => Task::future(async move {
+                match action.run(&text).await {
+                    Ok(ActionOutput::Text(output)) => Some(Message::PasteText(output)),
+                    Ok(ActionOutput::Launched) => Some(Message::RequestCloseHistoryWindow),
+                    Err(e) => {
+                        tracing::error!("Could not run action {action}\n{e:?}");
+                        None
+                    }
+                }
+            })
+            .and_then(Task::done),
+            Message::RefreshHistoryWindow => {
+                let Some(id) = self.get_history_window_id() else {
+                    return Task::none();
+                };
+                let db = self.db.clone();
+                Task::future(async move {
+                    repo::get_items(&db)
+                        .await
+                        .inspect_err(|e| tracing::error!("Could not refresh history\n{e:?}"))
+                        .ok()
+                })
+                .and_then(move |items| {
+                    Task::done(Message::HistoryWindowEvent(
+                        id,
+                        window::history::Message::Refreshed(items),
+                    ))
+                })
+            }
+            Message::PasteQueued(item) => {
+                self.paste_suffix = self.config.queue_paste_suffix.key();
+                Task::done(Message::RequestPaste(item, PasteMode::Formatted))
+            }
+            Message::ReplacePaste(item, pasted_chars) => {
+                let input = self.input.clone();
+                let replace = self.config.yank_pop_replace;
+                Task::perform(
+                    async move {
+                        let result = match replace {
+                            YankPopReplace::Undo => {
+                                input::tap_chord(input.as_ref(), &paste::UNDO_CHORD).await
+                            }
+                            YankPopReplace::SelectBack => {
+                                input::select_back(input.as_ref(), pasted_chars).await
+                            }
+                        };
+                        if let Err(e) = result {
+                            tracing::error!("Could not remove previous paste\n{e:?}");
+                        }
+                    },
+                    move |_| Message::RequestPaste(item.clone(), PasteMode::Formatted),
+                )
+            }
             Message::AppEvent(id, event) => match self.windows.get(&id) {
                 Some(window) => match window {
                     Window::History(_) => {
@@ -283,12 +1249,18 @@ impl App {
                                 iced::Event::Window(iced::window::Event::Unfocused) => {
                                     Task::done(Message::LooseFocus(id))
                                 }
+                                iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(
+                                    modifiers,
+                                )) => Task::done(Message::HistoryWindowEvent(
+                                    id,
+                                    window::history::Message::ModifiersChanged(modifiers),
+                                )),
                                 iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
-                                    key: _,
+                                    key,
                                     modified_key: _,
                                     physical_key,
                                     location: _,
-                                    modifiers: _,
+                                    modifiers,
                                     text: _,
                                 }) => match physical_key {
                                     key::Physical::Code(Code::ArrowDown) => {
@@ -304,14 +1276,109 @@ impl App {
                                         ))
                                     }
                                     key::Physical::Code(Code::Escape) => {
-                                        Task::done(Message::RequestCloseHistoryWindow)
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::Cancel,
+                                        ))
                                     }
                                     key::Physical::Code(Code::Enter) => {
+                                        let mode = if modifiers.control() {
+                                            PasteMode::TypeOut
+                                        } else if modifiers.shift() {
+                                            PasteMode::PlainText
+                                        } else {
+                                            PasteMode::Formatted
+                                        };
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::Paste(mode),
+                                        ))
+                                    }
+                                    key::Physical::Code(Code::Space) => {
+                                        let message = if modifiers.control() {
+                                            window::history::Message::ToggleSelected
+                                        } else {
+                                            window::history::Message::ToggleQueued
+                                        };
+                                        Task::done(Message::HistoryWindowEvent(id, message))
+                                    }
+                                    key::Physical::Code(Code::KeyA) if modifiers.control() => {
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::SelectAll,
+                                        ))
+                                    }
+                                    key::Physical::Code(Code::KeyE) if modifiers.control() => {
+                                        let message = if modifiers.shift() {
+                                            window::history::Message::ExternalEdit
+                                        } else {
+                                            window::history::Message::Edit
+                                        };
+                                        Task::done(Message::HistoryWindowEvent(id, message))
+                                    }
+                                    key::Physical::Code(Code::KeyT) if modifiers.control() => {
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::OpenPalette(
+                                                self.palette_actions(),
+                                            ),
+                                        ))
+                                    }
+                                    key::Physical::Code(Code::KeyF) if modifiers.control() => {
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::FocusSearch,
+                                        ))
+                                    }
+                                    key::Physical::Code(Code::KeyZ) if modifiers.control() => {
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::UndoDelete,
+                                        ))
+                                    }
+                                    key::Physical::Code(Code::Delete) => {
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::Delete,
+                                        ))
+                                    }
+                                    key::Physical::Code(Code::KeyK) if modifiers.control() => {
+                                        Task::done(Message::HistoryWindowEvent(
+                                            id,
+                                            window::history::Message::OpenActionMenu,
+                                        ))
+                                    }
+                                    // Only reaches here when no text input is focused
+                                    _ if key.as_ref() == Key::Character("?") => {
                                         Task::done(Message::HistoryWindowEvent(
                                             id,
-                                            window::history::Message::Paste,
+                                            window::history::Message::ToggleHelp,
                                         ))
                                     }
+                                    _ if modifiers.alt() => match key.as_ref() {
+                                        Key::Character(character) => {
+                                            let bound = self
+                                                .config
+                                                .smart_action_keys
+                                                .iter()
+                                                .filter(|binding| {
+                                                    character
+                                                        .to_lowercase()
+                                                        .chars()
+                                                        .eq(binding.key.to_lowercase())
+                                                })
+                                                .map(|binding| binding.action)
+                                                .collect::<Vec<_>>();
+                                            if bound.is_empty() {
+                                                return Task::none();
+                                            }
+                                            Task::done(Message::HistoryWindowEvent(
+                                                id,
+                                                window::history::Message::RunBoundAction(bound),
+                                            ))
+                                        }
+                                        _ => Task::none(),
+                                    },
                                     _ => Task::none(),
                                 },
                                 _ => Task::none(),
@@ -328,6 +1395,34 @@ impl App {
                             Task::none()
                         }
                     }
+                    Window::Editor(_) => match event {
+                        iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                            physical_key,
+                            modifiers,
+                            ..
+                        }) => {
+                            let message = match physical_key {
+                                key::Physical::Code(Code::KeyS) if modifiers.control() => {
+                                    Some(window::editor::Message::Save)
+                                }
+                                key::Physical::Code(Code::Enter) if modifiers.control() => {
+                                    Some(window::editor::Message::PasteOnce)
+                                }
+                                key::Physical::Code(Code::Escape) => {
+                                    Some(window::editor::Message::Cancel)
+                                }
+                                _ => None,
+                            };
+                            match message {
+                                Some(message) => {
+                                    Task::done(Message::EditorWindowEvent(id, message))
+                                }
+                                None => Task::none(),
+                            }
+                        }
+                        _ => Task::none(),
+                    },
+                    Window::Trash(_) | Window::YankPop(_) | Window::Error(_) => Task::none(),
                 },
                 None => Task::none(),
             },
@@ -335,11 +1430,34 @@ impl App {
                 self.toggle_shortcut = shortcut;
                 Task::none()
             }
+            Message::UpdateConfig(config) => {
+                if config.input_backend != self.config.input_backend {
+                    self.input = Self::create_input_backend(&config);
+                }
+                let reload_plugins = config.plugins != self.config.plugins;
+                self.config = config.clone();
+                let reload_task = if reload_plugins {
+                    Task::done(Message::ReloadPlugins)
+                } else {
+                    Task::none()
+                };
+                self.save_config(config).chain(reload_task)
+            }
+            Message::ConfigSaved => {
+                self.saving_config = false;
+                match self.queued_config.take() {
+                    Some(config) => self.save_config(config),
+                    None => Task::none(),
+                }
+            }
         }
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        let clipboard_event_subscription = Subscription::run(ClipboardListener::subscribe);
+        let clipboard_event_subscription = Subscription::run_with_id(
+            TypeId::of::<ClipboardListener>(),
+            ClipboardListener::subscribe(self.clipboard.change_watcher()),
+        );
         let global_event_subscription = Subscription::run(Self::subscribe_global_event);
         let tray_menu_event_subscription = Subscription::run(subscribe_tray_menu_event);
         let iced_event_subscription = event::listen_with(|event, status, id| {
@@ -361,6 +1479,168 @@ impl App {
         ])
     }
 
+    fn quick_paste_index(&self, modifiers: Modifiers, event: &rdev::Event) -> Option<u64> {
+        // Without modifiers, typing any digit would paste
+        if !self.config.quick_paste_enabled
+            || modifiers.is_empty()
+            || !self.config.quick_paste_modifiers.matches(modifiers)
+        {
+            return None;
+        }
+        match event.event_type {
+            rdev::EventType::KeyPress(key) => hotkey::digit(key).map(|n| n as u64 - 1),
+            _ => None,
+        }
+    }
+
+    fn snapshot_clipboard(&mut self) {
+        if self.config.restore_clipboard {
+            self.clipboard_snapshot = self
+                .clipboard
+                .read_all()
+                .inspect_err(|e| tracing::error!("Could not snapshot clipboard\n{e:?}"))
+                .ok();
+        }
+    }
+
+    fn update_tray(&self) {
+        if let Some(tray) = &self.tray {
+            tray.update_status(self.paste_queue.len(), self.collecting.is_some());
+        }
+    }
+
+    /// Writes the config, or queues it while another write is in flight
+    fn save_config(&mut self, config: Config) -> Task<Message> {
+        if !self.config_saves_enabled {
+            tracing::warn!("Not saving the config, it could not be loaded");
+            return Task::none();
+        }
+        if self.saving_config {
+            self.queued_config = Some(config);
+            return Task::none();
+        }
+        self.saving_config = true;
+        Task::future(async move {
+            if let Err(e) = config::save(config).await {
+                tracing::error!("Could not save config\n{e:?}");
+            }
+        })
+        .map(|_| Message::ConfigSaved)
+    }
+
+    /// Adds the copy to the history, or to the collecting entry in collect mode
+    fn store_capture(&mut self, capture: Capture) -> Task<Message> {
+        let Capture {
+            text,
+            contents,
+            tags,
+            kinds,
+            source_app,
+            copied_at,
+        } = capture;
+        if self.collecting.is_some() {
+            return self.collect(text);
+        }
+        let formats = contents
+            .iter()
+            .map(ClipboardContent::format)
+            .collect::<Vec<_>>();
+        let mut entry = CapturedEntry {
+            entry_id: 0,
+            text: text.clone(),
+            formats,
+            kinds,
+            tags: tags.clone(),
+            source_app: source_app.clone(),
+        };
+        let run_hooks = self
+            .config
+            .capture_hooks
+            .iter()
+            .any(|hook| hook.matches(&entry));
+        let source_app = (!source_app.is_empty()).then_some(source_app);
+        let db = self.db.clone();
+        Task::future(async move {
+            repo::add_item(&db, text, contents, tags, source_app, copied_at).await
+        })
+        .then(move |entry_id| match entry_id {
+            Ok(entry_id) if run_hooks => {
+                entry.entry_id = entry_id;
+                Task::done(Message::RunCaptureHooks(entry.clone()))
+            }
+            Ok(_) => Task::none(),
+            Err(e) => {
+                tracing::error!("Could not add entry\n{e:?}");
+                Task::none()
+            }
+        })
+    }
+
+    /// Appends the text to the collecting entry, or queues it while another write is in flight
+    fn collect(&mut self, text: String) -> Task<Message> {
+        let Some(collecting) = &mut self.collecting else {
+            return Task::none();
+        };
+        if collecting.writing {
+            collecting.queued.push(text);
+            return Task::none();
+        }
+        collecting.writing = true;
+        let entry_id = collecting.entry_id;
+        let db = self.db.clone();
+        let separator = self.config.collect_separator.clone();
+        Task::perform(
+            async move {
+                repo::collect(&db, entry_id, text, &separator)
+                    .await
+                    .inspect_err(|e| tracing::error!("Could not collect entry\n{e:?}"))
+                    .ok()
+            },
+            Message::CollectEntryUpdated,
+        )
+    }
+
+    fn when_modifiers_released(&mut self, message: Message) -> Task<Message> {
+        if self.global_modifiers.is_empty() {
+            Task::done(message)
+        } else {
+            self.deferred_message = Some(message);
+            Task::none()
+        }
+    }
+
+    fn fetch_item_at(
+        &self,
+        index: u64,
+        on_item: impl Fn(Option<Item>) -> Message + Send + 'static,
+    ) -> Task<Message> {
+        let db = self.db.clone();
+        Task::perform(
+            async move { repo::get_item_at(&db, index).await },
+            move |item| {
+                on_item(
+                    item.inspect_err(|e| tracing::error!("Could not retrieve item\n{e:?}"))
+                        .ok()
+                        .flatten(),
+                )
+            },
+        )
+    }
+
+    fn get_yank_pop_window(&self) -> Option<(iced::window::Id, &window::yank_pop::State)> {
+        self.windows.iter().find_map(|(id, window)| match window {
+            Window::YankPop(state) => Some((*id, state)),
+            _ => None,
+        })
+    }
+
+    fn get_yank_pop_window_mut(&mut self) -> Option<&mut window::yank_pop::State> {
+        self.windows.values_mut().find_map(|window| match window {
+            Window::YankPop(state) => Some(state),
+            _ => None,
+        })
+    }
+
     fn get_history_window_id(&self) -> Option<iced::window::Id> {
         self.windows
             .iter()
@@ -368,6 +1648,56 @@ impl App {
             .map(|(id, _)| *id)
     }
 
+    /// Pipelines, scripts and plugin transformations offered in the history palette
+    fn palette_actions(&self) -> Vec<PaletteAction> {
+        self.config
+            .pipelines
+            .iter()
+            .cloned()
+            .map(PaletteAction::Pipeline)
+            .chain(
+                self.scripts
+                    .transforms()
+                    .into_iter()
+                    .map(PaletteAction::Script),
+            )
+            .chain(
+                self.plugins
+                    .transforms()
+                    .into_iter()
+                    .map(PaletteAction::Plugin),
+            )
+            .collect()
+    }
+
+    fn get_history_window(&self) -> Option<&window::history::State> {
+        self.windows.values().find_map(|window| match window {
+            Window::History(state) => Some(state),
+            _ => None,
+        })
+    }
+
+    fn get_settings_window_id(&self) -> Option<iced::window::Id> {
+        self.windows
+            .iter()
+            .find(|(_, window)| matches!(window, Window::Settings(_)))
+            .map(|(id, _)| *id)
+    }
+
+    fn get_editor_window_id(&self) -> Option<iced::window::Id> {
+        self.windows
+            .iter()
+            .find(|(_, window)| matches!(window, Window::Editor(_)))
+            .map(|(id, _)| *id)
+    }
+
+    fn get_trash_window_id(&self) -> Option<iced::window::Id> {
+        self.windows
+            .iter()
+            .find(|(_, window)| matches!(window, Window::Trash(_)))
+            .map(|(id, _)| *id)
+    }
+
     fn subscribe_global_event() -> impl Stream<Item = Message> {
         stream::channel(ASYNC_CHANNEL_SIZE, |mut sender| async move {
             let (tx, mut rx) = mpsc::channel(ASYNC_CHANNEL_SIZE);
@@ -429,7 +1759,272 @@ impl App {
             Some(Window::Settings(state)) => state
                 .view()
                 .map(move |message| Message::SettingsWindowEvent(id, message)),
+            Some(Window::Editor(state)) => state
+                .view()
+                .map(move |message| Message::EditorWindowEvent(id, message)),
+            Some(Window::Trash(state)) => state
+                .view()
+                .map(move |message| Message::TrashWindowEvent(id, message)),
+            Some(Window::YankPop(state)) => state.view(),
+            Some(Window::Error(state)) => state.view(id),
             None => horizontal_space().into(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use iced::futures::StreamExt;
+    use iced_runtime::Action;
+    use migration::{Migrator, MigratorTrait};
+    use rdev::EventType;
+    use sea_orm::{ConnectOptions, Database};
+
+    use super::*;
+    use crate::{
+        clipboard::MemoryClipboard,
+        input::{KeyboardLayout, RecordingInput},
+    };
+
+    async fn memory_db() -> DatabaseConnection {
+        // Every connection to an in-memory database gets its own, a single one is kept
+        let mut options = ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let db = Database::connect(options).await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+        db
+    }
+
+    /// App on in-memory backends, the startup task is dropped as it opens the user database
+    async fn app(clipboard: &MemoryClipboard, input: &RecordingInput) -> App {
+        let (mut app, _) = App::with_backends(
+            Box::new(clipboard.clone()),
+            Arc::new(input.clone()),
+            Config::default(),
+        );
+        app.db = memory_db().await;
+        app
+    }
+
+    async fn dispatch(app: &mut App, message: Message) {
+        run(app, Task::done(message)).await;
+    }
+
+    /// Runs the task and every message it produces, other runtime actions are dropped
+    async fn run(app: &mut App, task: Task<Message>) {
+        let mut tasks = VecDeque::from([task]);
+        while let Some(task) = tasks.pop_front() {
+            let Some(mut actions) = iced_runtime::task::into_stream(task) else {
+                continue;
+            };
+            while let Some(action) = actions.next().await {
+                if let Action::Output(message) = action {
+                    tasks.push_back(app.update(message));
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn clipboard_event_adds_entry() {
+        let clipboard = MemoryClipboard::new();
+        let mut app = app(&clipboard, &RecordingInput::new()).await;
+
+        clipboard.copy(vec![
+            ClipboardContent::Text("hello".to_owned()),
+            ClipboardContent::Html("<b>hello</b>".to_owned()),
+        ]);
+        dispatch(&mut app, Message::ClipboardEvent).await;
+
+        let items = repo::get_items(&app.db).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].entry.data, "hello");
+        assert_eq!(
+            items[0].formats,
+            vec![ClipboardContent::Html("<b>hello</b>".to_owned())]
+        );
+    }
+
+    #[tokio::test]
+    async fn clipboard_event_without_text_is_ignored() {
+        let clipboard = MemoryClipboard::new();
+        let mut app = app(&clipboard, &RecordingInput::new()).await;
+
+        clipboard.copy(vec![ClipboardContent::Files(vec!["/tmp/a".to_owned()])]);
+        dispatch(&mut app, Message::ClipboardEvent).await;
+
+        assert!(repo::get_items(&app.db).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_clipboard_item_writes_contents_and_skips_own_change() {
+        let clipboard = MemoryClipboard::new();
+        let mut app = app(&clipboard, &RecordingInput::new()).await;
+        clipboard.copy(vec![
+            ClipboardContent::Text("first".to_owned()),
+            ClipboardContent::Html("<i>first</i>".to_owned()),
+        ]);
+        dispatch(&mut app, Message::ClipboardEvent).await;
+        clipboard.copy(vec![ClipboardContent::Text("second".to_owned())]);
+        dispatch(&mut app, Message::ClipboardEvent).await;
+        let first = repo::get_item_at(&app.db, 1).await.unwrap().unwrap();
+
+        dispatch(
+            &mut app,
+            Message::SetClipboardItem(first.clone(), PasteMode::PlainText),
+        )
+        .await;
+        assert_eq!(
+            clipboard.contents(),
+            vec![ClipboardContent::Text("first".to_owned())]
+        );
+
+        // The change notification of our own write must not be captured again
+        dispatch(&mut app, Message::ClipboardEvent).await;
+        let items = repo::get_items(&app.db).await.unwrap();
+        assert_eq!(items.len(), 2);
+        let first = items
+            .iter()
+            .find(|item| item.entry.id == first.entry.id)
+            .unwrap();
+        assert_eq!(first.entry.use_count, 1);
+    }
+
+    #[tokio::test]
+    async fn set_clipboard_item_keeps_formats() {
+        let clipboard = MemoryClipboard::new();
+        let mut app = app(&clipboard, &RecordingInput::new()).await;
+        let contents = vec![
+            ClipboardContent::Text("rich".to_owned()),
+            ClipboardContent::Rtf("{\\rtf1 rich}".to_owned()),
+        ];
+        clipboard.copy(contents.clone());
+        dispatch(&mut app, Message::ClipboardEvent).await;
+        let item = repo::get_item_at(&app.db, 0).await.unwrap().unwrap();
+        clipboard.copy(vec![ClipboardContent::Text("other".to_owned())]);
+
+        dispatch(
+            &mut app,
+            Message::SetClipboardItem(item, PasteMode::Formatted),
+        )
+        .await;
+
+        assert_eq!(clipboard.contents(), contents);
+    }
+
+    #[tokio::test]
+    async fn simulate_paste_taps_paste_chord() {
+        let input = RecordingInput::new();
+        let mut app = app(&MemoryClipboard::new(), &input).await;
+
+        dispatch(&mut app, Message::SimulatePaste).await;
+
+        assert_eq!(
+            input.events(),
+            vec![
+                EventType::KeyPress(paste::DEFAULT_PASTE_CHORD[0]),
+                EventType::KeyPress(paste::DEFAULT_PASTE_CHORD[1]),
+                EventType::KeyRelease(paste::DEFAULT_PASTE_CHORD[1]),
+                EventType::KeyRelease(paste::DEFAULT_PASTE_CHORD[0]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn simulate_paste_taps_suffix_after_chord() {
+        let input = RecordingInput::new();
+        let mut app = app(&MemoryClipboard::new(), &input).await;
+        app.paste_suffix = Some(rdev::Key::Return);
+
+        dispatch(&mut app, Message::SimulatePaste).await;
+
+        assert_eq!(
+            input.events()[4..],
+            [
+                EventType::KeyPress(rdev::Key::Return),
+                EventType::KeyRelease(rdev::Key::Return),
+            ]
+        );
+        assert_eq!(app.paste_suffix, None);
+    }
+
+    #[tokio::test]
+    async fn type_out_types_text_without_paste_chord() {
+        let input = RecordingInput::new();
+        let mut app = app(&MemoryClipboard::new(), &input).await;
+        app.config.keyboard_layout = KeyboardLayout::Qwerty;
+
+        dispatch(&mut app, Message::TypeOut("ok".to_owned())).await;
+
+        assert_eq!(
+            input.events(),
+            vec![
+                EventType::KeyPress(rdev::Key::KeyO),
+                EventType::KeyRelease(rdev::Key::KeyO),
+                EventType::KeyPress(rdev::Key::KeyK),
+                EventType::KeyRelease(rdev::Key::KeyK),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn type_out_waits_for_modifiers_release() {
+        let clipboard = MemoryClipboard::new();
+        let input = RecordingInput::new();
+        let mut app = app(&clipboard, &input).await;
+        app.config.keyboard_layout = KeyboardLayout::Qwerty;
+        clipboard.copy(vec![ClipboardContent::Text("ok".to_owned())]);
+        dispatch(&mut app, Message::ClipboardEvent).await;
+        let item = repo::get_item_at(&app.db, 0).await.unwrap().unwrap();
+        let key_event = |event_type| rdev::Event {
+            time: std::time::SystemTime::now(),
+            name: None,
+            event_type,
+        };
+
+        app.global_modifiers = Modifiers::CTRL;
+        dispatch(&mut app, Message::RequestPaste(item, PasteMode::TypeOut)).await;
+        assert!(input.events().is_empty());
+
+        dispatch(
+            &mut app,
+            Message::GlobalEvent(
+                Modifiers::empty(),
+                key_event(EventType::KeyRelease(rdev::Key::ControlLeft)),
+            ),
+        )
+        .await;
+        assert_eq!(
+            input.events(),
+            vec![
+                EventType::KeyPress(rdev::Key::KeyO),
+                EventType::KeyRelease(rdev::Key::KeyO),
+                EventType::KeyPress(rdev::Key::KeyK),
+                EventType::KeyRelease(rdev::Key::KeyK),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn collect_mode_appends_copies_made_during_first_write() {
+        let clipboard = MemoryClipboard::new();
+        let mut app = app(&clipboard, &RecordingInput::new()).await;
+        dispatch(&mut app, Message::ToggleCollectMode).await;
+
+        // Both copies arrive before the collecting entry is created, the second one is done
+        // processing first
+        clipboard.copy(vec![ClipboardContent::Text("a".to_owned())]);
+        let first = app.update(Message::ClipboardEvent);
+        clipboard.copy(vec![ClipboardContent::Text("b".to_owned())]);
+        let second = app.update(Message::ClipboardEvent);
+        run(&mut app, second).await;
+        assert!(repo::get_items(&app.db).await.unwrap().is_empty());
+        run(&mut app, first).await;
+        clipboard.copy(vec![ClipboardContent::Text("c".to_owned())]);
+        dispatch(&mut app, Message::ClipboardEvent).await;
+
+        let items = repo::get_items(&app.db).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].entry.data, "a\nb\nc");
+    }
+}
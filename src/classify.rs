@@ -0,0 +1,443 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::color;
+
+/// Kind of content detected in an entry, an entry can have several
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    Url,
+    Email,
+    Path,
+    Json,
+    Code,
+    Color,
+    Number,
+    Markdown,
+}
+
+impl ContentKind {
+    pub const ALL: [ContentKind; 8] = [
+        ContentKind::Url,
+        ContentKind::Email,
+        ContentKind::Path,
+        ContentKind::Json,
+        ContentKind::Code,
+        ContentKind::Color,
+        ContentKind::Number,
+        ContentKind::Markdown,
+    ];
+
+    /// Name stored in the database and used by `type:` filters
+    pub fn name(self) -> &'static str {
+        match self {
+            ContentKind::Url => "url",
+            ContentKind::Email => "email",
+            ContentKind::Path => "path",
+            ContentKind::Json => "json",
+            ContentKind::Code => "code",
+            ContentKind::Color => "color",
+            ContentKind::Number => "number",
+            ContentKind::Markdown => "markdown",
+        }
+    }
+
+    /// Parses the comma separated names stored in the database, unknown ones are skipped
+    pub fn parse_list(kinds: &str) -> Vec<ContentKind> {
+        kinds
+            .split(',')
+            .filter_map(|kind| kind.parse().ok())
+            .collect()
+    }
+}
+
+impl FromStr for ContentKind {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ContentKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or(())
+    }
+}
+
+impl Display for ContentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Classification {
+    pub kinds: Vec<ContentKind>,
+    /// Programming language, only set along with `ContentKind::Code`
+    pub language: Option<&'static str>,
+}
+
+impl Classification {
+    pub fn kinds_string(&self) -> String {
+        self.kinds
+            .iter()
+            .map(|kind| kind.name())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("Valid classification pattern")
+}
+
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| regex(r"(?i)^(?:[a-z][a-z0-9+.-]*://\S+|www\.\S+\.[a-z]{2,}\S*)$"));
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| regex(r"(?i)^(?:mailto:)?[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}$"));
+static PATH: LazyLock<Regex> = LazyLock::new(|| {
+    regex(r#"^(?:(?:~|\.{1,2})?(?:/[^/\x00]+)+/?|(?:[A-Za-z]:|\\\\[^\\\s]+)(?:\\[^\\<>:"|?*]*)+)$"#)
+});
+static NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+    regex(
+        r"^(?:[+-]?(?:\d{1,3}(?:[,_]\d{3})+|\d+)(?:\.\d+)?(?:[eE][+-]?\d+)?|0[xX][0-9a-fA-F]+|0[bB][01]+)$",
+    )
+});
+
+/// Patterns hinting at each language, the one matching the most wins.
+/// On a tie the language listed first wins.
+const LANGUAGE_PATTERNS: &[(&str, &[&str])] = &[
+    (
+        "rust",
+        &[
+            r"\bfn\s+\w+\s*[<(]",
+            r"\blet\s+mut\b",
+            r"\bimpl\b.*\{",
+            r"\bpub\s+(?:fn|struct|enum|mod|use)\b",
+            r"#\[derive\(",
+            r"\w+::\w+",
+            r"\)\s*->\s*[\w&(]",
+            r"\b\w+!\(",
+        ],
+    ),
+    (
+        "python",
+        &[
+            r"(?m)^\s*def\s+\w+\s*\(.*\)\s*(?:->.*)?:\s*$",
+            r"(?m)^\s*(?:from\s+[\w.]+\s+)?import\s+\w+",
+            r"\bself\.\w+",
+            r"(?m)^\s*(?:elif|except|finally)\b",
+            r"\bprint\(",
+            r"(?m)^\s*class\s+\w+(?:\(.*\))?:\s*$",
+            r"\b(?:None|True|False)\b",
+        ],
+    ),
+    (
+        "javascript",
+        &[
+            r"\b(?:const|let|var)\s+\w+\s*=",
+            r"\bfunction\s*\w*\s*\(",
+            r"=>",
+            r"\bconsole\.\w+\(",
+            r"\brequire\(",
+            r"\bexport\s+(?:default|const|function)\b",
+            r"===|!==",
+        ],
+    ),
+    (
+        "typescript",
+        &[
+            r"\b(?:const|let|var)\s+\w+\s*[=:]",
+            r"=>",
+            r"\bexport\s+(?:default|const|function|interface|type)\b",
+            r"\binterface\s+\w+\s*\{",
+            r":\s*(?:string|number|boolean|any|void)\b",
+            r"\btype\s+\w+\s*=",
+        ],
+    ),
+    (
+        "go",
+        &[
+            r"(?m)^package\s+\w+",
+            r"\bfunc\s+(?:\(\w+\s+\*?\w+\)\s*)?\w+\(",
+            r":=",
+            r"\bfmt\.\w+\(",
+            r"\bgo\s+func\b",
+            r"\bchan\b",
+        ],
+    ),
+    (
+        "c",
+        &[
+            r#"(?m)^#include\s*[<"]"#,
+            r"\bint\s+main\s*\(",
+            r"\bprintf\s*\(",
+            r"\b(?:malloc|free|sizeof)\s*\(",
+            r"\w->\w",
+            r"\b(?:unsigned|struct)\s+\w+",
+        ],
+    ),
+    (
+        "cpp",
+        &[
+            r#"(?m)^#include\s*[<"]"#,
+            r"\bstd::\w+",
+            r"\bcout\s*<<",
+            r"\b(?:template|namespace)\b",
+            r"\bnullptr\b",
+        ],
+    ),
+    (
+        "java",
+        &[
+            r"\bpublic\s+(?:static\s+)?(?:class|void|final)\b",
+            r"\bSystem\.out\.print",
+            r"\bimport\s+java\.",
+            r"@Override",
+            r"\bnew\s+[A-Z]\w*\s*\(",
+            r"\bprivate\s+\w+\s+\w+\s*;",
+        ],
+    ),
+    (
+        "shell",
+        &[
+            r"(?m)^#!.*\b(?:ba|z)?sh\b",
+            r"(?m)^\s*(?:if|then|fi|do|done|esac)\b",
+            r"\$\{\w+\}|\$[A-Za-z_]\w*",
+            r"\becho\s",
+            r"\|\s*(?:grep|awk|sed|xargs)\b",
+            r"(?m)^\s*(?:sudo|apt|cd|ls|export)\s",
+        ],
+    ),
+    (
+        "sql",
+        &[
+            r"(?i)^\s*(?:select|insert\s+into|update|delete\s+from|create\s+table)\b",
+            r"(?i)\bfrom\s+\w+",
+            r"(?i)\bwhere\b",
+            r"(?i)\b(?:join|group\s+by|order\s+by)\b",
+        ],
+    ),
+    (
+        "html",
+        &[
+            r"(?i)<!DOCTYPE html",
+            r"</(?:div|span|p|a|body|html|head|ul|li|table)>",
+            r#"<\w+(?:\s+[\w-]+="[^"]*")+\s*/?>"#,
+        ],
+    ),
+    (
+        "css",
+        &[
+            r"(?m)^\s*[.#]?[\w-]+(?:\s*[,>+~]\s*[.#]?[\w-]+)*\s*\{",
+            r"(?m)^\s*[\w-]+\s*:\s*[^;]+;\s*$",
+            r"\d(?:px|em|rem|vh|vw)\b",
+        ],
+    ),
+];
+
+/// Patterns the text has to match before a language can score at all, prose easily uses
+/// the words its other patterns look for
+const LANGUAGE_PREREQUISITES: &[(&str, &str)] = &[(
+    "sql",
+    r"(?i)^\s*(?:select|insert|update|delete|with|create)\b",
+)];
+
+static LANGUAGES: LazyLock<Vec<(&str, Option<Regex>, Vec<Regex>)>> = LazyLock::new(|| {
+    LANGUAGE_PATTERNS
+        .iter()
+        .map(|(language, patterns)| {
+            let prerequisite = LANGUAGE_PREREQUISITES
+                .iter()
+                .find(|(name, _)| name == language)
+                .map(|(_, pattern)| regex(pattern));
+            (
+                *language,
+                prerequisite,
+                patterns.iter().copied().map(regex).collect(),
+            )
+        })
+        .collect()
+});
+
+/// Markdown syntax, prose matching at least [`MIN_MARKDOWN_SCORE`] of them is markdown
+const MARKDOWN_PATTERNS: &[&str] = &[
+    r"(?m)^#{1,6}\s+\S",
+    r"(?m)^\s*(?:[-*+]|\d+\.)\s+\S",
+    r"\[[^\]]+\]\([^)\s]+\)",
+    r"(?m)^```",
+    r"\*\*[^*\n]+\*\*|__[^_\n]+__",
+    r"`[^`\n]+`",
+    r"(?m)^>\s",
+];
+
+static MARKDOWN: LazyLock<Vec<Regex>> =
+    LazyLock::new(|| MARKDOWN_PATTERNS.iter().copied().map(regex).collect());
+
+/// At least this many patterns of a language have to match for text to be code
+const MIN_LANGUAGE_SCORE: usize = 2;
+/// A single backtick or list dash is common in plain prose
+const MIN_MARKDOWN_SCORE: usize = 2;
+
+fn detect_language(text: &str) -> Option<&'static str> {
+    let mut best = None;
+    let mut best_score = MIN_LANGUAGE_SCORE - 1;
+    for (language, prerequisite, patterns) in LANGUAGES.iter() {
+        if prerequisite
+            .as_ref()
+            .is_some_and(|prerequisite| !prerequisite.is_match(text))
+        {
+            continue;
+        }
+        let score = patterns
+            .iter()
+            .filter(|pattern| pattern.is_match(text))
+            .count();
+        if score > best_score {
+            best = Some(*language);
+            best_score = score;
+        }
+    }
+    best
+}
+
+pub fn classify(text: &str) -> Classification {
+    let mut classification = Classification::default();
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return classification;
+    }
+
+    if !trimmed.contains('\n') {
+        if URL.is_match(trimmed) {
+            classification.kinds.push(ContentKind::Url);
+        } else if EMAIL.is_match(trimmed) {
+            classification.kinds.push(ContentKind::Email);
+        } else if PATH.is_match(trimmed) {
+            classification.kinds.push(ContentKind::Path);
+        }
+        if color::parse(trimmed).is_some() {
+            classification.kinds.push(ContentKind::Color);
+        }
+        if NUMBER.is_match(trimmed) {
+            classification.kinds.push(ContentKind::Number);
+        }
+    }
+
+    if trimmed.starts_with(['{', '['])
+        && serde_json::from_str::<serde::de::IgnoredAny>(trimmed).is_ok()
+    {
+        classification.kinds.push(ContentKind::Json);
+    } else if classification.kinds.is_empty() {
+        if let Some(language) = detect_language(trimmed) {
+            classification.kinds.push(ContentKind::Code);
+            classification.language = Some(language);
+        } else if MARKDOWN
+            .iter()
+            .filter(|pattern| pattern.is_match(trimmed))
+            .count()
+            >= MIN_MARKDOWN_SCORE
+        {
+            classification.kinds.push(ContentKind::Markdown);
+        }
+    }
+    classification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<ContentKind> {
+        classify(text).kinds
+    }
+
+    fn language(text: &str) -> Option<&'static str> {
+        classify(text).language
+    }
+
+    #[test]
+    fn classifies_single_line_kinds() {
+        assert_eq!(kinds("https://example.com/path"), vec![ContentKind::Url]);
+        assert_eq!(kinds("john.doe@example.com"), vec![ContentKind::Email]);
+        assert_eq!(kinds("/home/user/notes.txt"), vec![ContentKind::Path]);
+        assert_eq!(kinds("#ff8800"), vec![ContentKind::Color]);
+        assert_eq!(kinds("1,234.56"), vec![ContentKind::Number]);
+    }
+
+    #[test]
+    fn classifies_json() {
+        assert_eq!(kinds(r#"{"a": [1, 2]}"#), vec![ContentKind::Json]);
+    }
+
+    #[test]
+    fn classifies_markdown() {
+        assert_eq!(
+            kinds("# Title\n\n- first item\n- second item"),
+            vec![ContentKind::Markdown]
+        );
+    }
+
+    #[test]
+    fn detects_languages() {
+        let cases = [
+            ("rust", "fn main() {\n    let mut x = 1;\n}"),
+            ("python", "def greet(name):\n    print(name)"),
+            (
+                "javascript",
+                "const add = (a, b) => a + b;\nconsole.log(add(1, 2));",
+            ),
+            (
+                "typescript",
+                "interface User {\n  name: string;\n}\nconst user: User = { name: 'a' };",
+            ),
+            (
+                "go",
+                "package main\n\nfunc main() {\n\tx := 1\n\tfmt.Println(x)\n}",
+            ),
+            (
+                "c",
+                "#include <stdio.h>\n\nint main(void) {\n    printf(\"hi\");\n}",
+            ),
+            (
+                "cpp",
+                "#include <iostream>\n\nint main() {\n    std::cout << \"hi\";\n}",
+            ),
+            (
+                "java",
+                "public class Main {\n    @Override\n    public String toString() {\n        return new String(\"a\");\n    }\n}",
+            ),
+            ("shell", "echo $HOME | grep home"),
+            ("sql", "SELECT id FROM users WHERE id = 1"),
+            (
+                "html",
+                "<!DOCTYPE html>\n<html>\n<body><p class=\"intro\">Hi</p></body>\n</html>",
+            ),
+            ("css", ".button {\n  color: red;\n  padding: 4px;\n}"),
+        ];
+        for (expected, text) in cases {
+            assert_eq!(language(text), Some(expected), "{text}");
+            assert_eq!(kinds(text), vec![ContentKind::Code], "{text}");
+        }
+    }
+
+    #[test]
+    fn prose_using_sql_words_is_not_code() {
+        assert!(kinds("Please select the file from the menu where you saved it.").is_empty());
+    }
+
+    #[test]
+    fn prose_with_a_price_is_not_shell() {
+        assert!(kinds("I heard an echo and paid $20 for it.").is_empty());
+    }
+
+    #[test]
+    fn prose_with_a_single_markdown_feature_is_not_markdown() {
+        assert!(kinds("Use `cargo build` to compile it.").is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc::{error::TrySendError, Sender};
+
+use super::{ChangeWatcher, ClipboardBackend, ClipboardContent, ClipboardFormat};
+
+/// Clipboard living entirely in memory, to drive `App::update` without a display server.
+#[derive(Clone, Default)]
+pub struct MemoryClipboard(Arc<Mutex<Inner>>);
+
+#[derive(Default)]
+struct Inner {
+    contents: Vec<ClipboardContent>,
+    listeners: Vec<Sender<()>>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> MemoryClipboard {
+        Default::default()
+    }
+
+    /// Simulates another application copying something.
+    pub fn copy(&self, contents: Vec<ClipboardContent>) {
+        self.write(contents).unwrap();
+    }
+
+    pub fn contents(&self) -> Vec<ClipboardContent> {
+        self.0.lock().unwrap().contents.clone()
+    }
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn available_formats(&self) -> anyhow::Result<Vec<ClipboardFormat>> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .contents
+            .iter()
+            .map(ClipboardContent::format)
+            .collect())
+    }
+
+    fn read(&self, formats: &[ClipboardFormat]) -> anyhow::Result<Vec<ClipboardContent>> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .contents
+            .iter()
+            .filter(|content| formats.contains(&content.format()))
+            .cloned()
+            .collect())
+    }
+
+    fn write(&self, contents: Vec<ClipboardContent>) -> anyhow::Result<()> {
+        let mut inner = self.0.lock().unwrap();
+        inner.contents = contents;
+        // Like a real clipboard, our own writes are notified too
+        inner
+            .listeners
+            .retain(|listener| !matches!(listener.try_send(()), Err(TrySendError::Closed(_))));
+        Ok(())
+    }
+
+    fn change_watcher(&self) -> ChangeWatcher {
+        let inner = self.0.clone();
+        Arc::new(move |tx| inner.lock().unwrap().listeners.push(tx))
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use iced::{
+    futures::{SinkExt, Stream},
+    stream,
+};
+use tokio::sync::mpsc::{self, Sender};
+
+use crate::{app::Message, utils::ASYNC_CHANNEL_SIZE};
+
+#[cfg(test)]
+mod memory;
+mod self_writes;
+mod system;
+
+#[cfg(test)]
+pub use memory::MemoryClipboard;
+pub use self_writes::SelfWrites;
+pub use system::SystemClipboard;
+
+pub const ALL_FORMATS: [ClipboardFormat; 4] = [
+    ClipboardFormat::Text,
+    ClipboardFormat::Html,
+    ClipboardFormat::Rtf,
+    ClipboardFormat::Files,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardFormat {
+    Text,
+    Html,
+    Rtf,
+    Files,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClipboardContent {
+    Text(String),
+    Html(String),
+    Rtf(String),
+    Files(Vec<String>),
+}
+
+impl ClipboardContent {
+    pub fn format(&self) -> ClipboardFormat {
+        match self {
+            ClipboardContent::Text(_) => ClipboardFormat::Text,
+            ClipboardContent::Html(_) => ClipboardFormat::Html,
+            ClipboardContent::Rtf(_) => ClipboardFormat::Rtf,
+            ClipboardContent::Files(_) => ClipboardFormat::Files,
+        }
+    }
+}
+
+/// Starts delivering a `()` on the given sender every time the clipboard changes.
+pub type ChangeWatcher = Arc<dyn Fn(Sender<()>) + Send + Sync>;
+
+pub trait ClipboardBackend {
+    fn available_formats(&self) -> anyhow::Result<Vec<ClipboardFormat>>;
+
+    /// Reads every requested format currently present on the clipboard, missing ones are skipped.
+    fn read(&self, formats: &[ClipboardFormat]) -> anyhow::Result<Vec<ClipboardContent>>;
+
+    /// Replaces the clipboard with the given representations of a single item.
+    fn write(&self, contents: Vec<ClipboardContent>) -> anyhow::Result<()>;
+
+    fn change_watcher(&self) -> ChangeWatcher;
+
+    fn read_all(&self) -> anyhow::Result<Vec<ClipboardContent>> {
+        self.read(&self.available_formats()?)
+    }
+}
+
+pub fn find_text(contents: &[ClipboardContent]) -> Option<&str> {
+    contents.iter().find_map(|content| match content {
+        ClipboardContent::Text(text) => Some(text.as_str()),
+        _ => None,
+    })
+}
+
+pub struct ClipboardListener;
+
+impl ClipboardListener {
+    pub fn subscribe(watcher: ChangeWatcher) -> impl Stream<Item = Message> {
+        stream::channel(ASYNC_CHANNEL_SIZE, |mut output| async move {
+            let (tx, mut rx) = mpsc::channel(ASYNC_CHANNEL_SIZE);
+            watcher(tx);
+
+            loop {
+                rx.recv().await.unwrap();
+                output.send(Message::ClipboardEvent).await.unwrap();
+            }
+        })
+    }
+}
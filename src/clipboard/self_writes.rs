@@ -0,0 +1,45 @@
+use std::{
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+    time::{Duration, Instant},
+};
+
+use super::{find_text, ClipboardContent};
+
+/// Change events caused by a write arrive well within this window.
+const SELF_WRITE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Remembers what the app wrote to the clipboard so the change events it causes
+/// can be told apart from copies made by the user.
+#[derive(Debug, Default)]
+pub struct SelfWrites(VecDeque<(u64, Instant)>);
+
+impl SelfWrites {
+    pub fn record(&mut self, contents: &[ClipboardContent]) {
+        self.0.push_back((fingerprint(contents), Instant::now()));
+    }
+
+    /// Consumes the matching write if the clipboard now holds something the app wrote.
+    pub fn is_self_write(&mut self, contents: &[ClipboardContent]) -> bool {
+        self.0
+            .retain(|(_, written_at)| written_at.elapsed() < SELF_WRITE_WINDOW);
+        let fingerprint = fingerprint(contents);
+        match self.0.iter().position(|(written, _)| *written == fingerprint) {
+            Some(index) => {
+                self.0.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Other formats are often rewritten by the system, text is the most stable representation.
+fn fingerprint(contents: &[ClipboardContent]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match find_text(contents) {
+        Some(text) => text.hash(&mut hasher),
+        None => contents.hash(&mut hasher),
+    }
+    hasher.finish()
+}
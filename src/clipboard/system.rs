@@ -0,0 +1,91 @@
+use std::{sync::Arc, thread};
+
+use clipboard_rs::{
+    Clipboard, ClipboardContext, ClipboardHandler, ClipboardWatcher, ClipboardWatcherContext,
+    ContentFormat,
+};
+use tokio::sync::mpsc::Sender;
+
+use super::{ChangeWatcher, ClipboardBackend, ClipboardContent, ClipboardFormat, ALL_FORMATS};
+
+pub struct SystemClipboard(ClipboardContext);
+
+impl SystemClipboard {
+    pub fn new() -> anyhow::Result<SystemClipboard> {
+        Ok(SystemClipboard(
+            ClipboardContext::new().map_err(|e| anyhow::anyhow!(e))?,
+        ))
+    }
+}
+
+fn content_format(format: ClipboardFormat) -> ContentFormat {
+    match format {
+        ClipboardFormat::Text => ContentFormat::Text,
+        ClipboardFormat::Html => ContentFormat::Html,
+        ClipboardFormat::Rtf => ContentFormat::Rtf,
+        ClipboardFormat::Files => ContentFormat::Files,
+    }
+}
+
+impl ClipboardBackend for SystemClipboard {
+    fn available_formats(&self) -> anyhow::Result<Vec<ClipboardFormat>> {
+        Ok(ALL_FORMATS
+            .into_iter()
+            .filter(|format| self.0.has(content_format(*format)))
+            .collect())
+    }
+
+    fn read(&self, formats: &[ClipboardFormat]) -> anyhow::Result<Vec<ClipboardContent>> {
+        let mut contents = Vec::with_capacity(formats.len());
+        for format in formats {
+            if !self.0.has(content_format(*format)) {
+                continue;
+            }
+            let content = match format {
+                ClipboardFormat::Text => self.0.get_text().map(ClipboardContent::Text),
+                ClipboardFormat::Html => self.0.get_html().map(ClipboardContent::Html),
+                ClipboardFormat::Rtf => self.0.get_rich_text().map(ClipboardContent::Rtf),
+                ClipboardFormat::Files => self.0.get_files().map(ClipboardContent::Files),
+            }
+            .map_err(|e| anyhow::anyhow!(e))?;
+            contents.push(content);
+        }
+        Ok(contents)
+    }
+
+    fn write(&self, contents: Vec<ClipboardContent>) -> anyhow::Result<()> {
+        self.0
+            .set(
+                contents
+                    .into_iter()
+                    .map(|content| match content {
+                        ClipboardContent::Text(text) => clipboard_rs::ClipboardContent::Text(text),
+                        ClipboardContent::Html(html) => clipboard_rs::ClipboardContent::Html(html),
+                        ClipboardContent::Rtf(rtf) => clipboard_rs::ClipboardContent::Rtf(rtf),
+                        ClipboardContent::Files(files) => {
+                            clipboard_rs::ClipboardContent::Files(files)
+                        }
+                    })
+                    .collect(),
+            )
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn change_watcher(&self) -> ChangeWatcher {
+        Arc::new(|tx| {
+            thread::spawn(|| {
+                let mut clipboard_watcher: ClipboardWatcherContext<ChangeHandler> =
+                    ClipboardWatcherContext::new().unwrap();
+                clipboard_watcher.add_handler(ChangeHandler(tx)).start_watch();
+            });
+        })
+    }
+}
+
+struct ChangeHandler(Sender<()>);
+
+impl ClipboardHandler for ChangeHandler {
+    fn on_clipboard_change(&mut self) {
+        self.0.blocking_send(()).unwrap();
+    }
+}
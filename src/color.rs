@@ -0,0 +1,281 @@
+//! CSS color values, parsed into iced colors and written back in any of the supported formats
+
+use std::fmt::{self, Display};
+
+use iced::Color;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorFormat {
+    Hex,
+    Rgb,
+    Hsl,
+    Oklch,
+}
+
+impl ColorFormat {
+    pub const ALL: [ColorFormat; 4] = [
+        ColorFormat::Hex,
+        ColorFormat::Rgb,
+        ColorFormat::Hsl,
+        ColorFormat::Oklch,
+    ];
+
+    pub fn format(self, color: Color) -> String {
+        let opaque = color.a >= 1.0;
+        match self {
+            ColorFormat::Hex => {
+                let [r, g, b, a] = color.into_rgba8();
+                if opaque {
+                    format!("#{r:02x}{g:02x}{b:02x}")
+                } else {
+                    format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+                }
+            }
+            ColorFormat::Rgb => {
+                let [r, g, b, _] = color.into_rgba8();
+                if opaque {
+                    format!("rgb({r}, {g}, {b})")
+                } else {
+                    format!("rgba({r}, {g}, {b}, {})", round(color.a, 3))
+                }
+            }
+            ColorFormat::Hsl => {
+                let (h, s, l) = rgb_to_hsl(color);
+                let (h, s, l) = (round(h, 1), round(s * 100.0, 1), round(l * 100.0, 1));
+                if opaque {
+                    format!("hsl({h}, {s}%, {l}%)")
+                } else {
+                    format!("hsla({h}, {s}%, {l}%, {})", round(color.a, 3))
+                }
+            }
+            ColorFormat::Oklch => {
+                let (l, c, h) = rgb_to_oklch(color);
+                let (l, c, h) = (round(l * 100.0, 2), round(c, 4), round(h, 2));
+                if opaque {
+                    format!("oklch({l}% {c} {h})")
+                } else {
+                    format!("oklch({l}% {c} {h} / {})", round(color.a, 3))
+                }
+            }
+        }
+    }
+}
+
+impl Display for ColorFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorFormat::Hex => "hex",
+            ColorFormat::Rgb => "rgb()",
+            ColorFormat::Hsl => "hsl()",
+            ColorFormat::Oklch => "oklch()",
+        })
+    }
+}
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`, `hsl()`, `hsla()` and
+/// `oklch()`, with comma or space separated components, along with the format it is written in
+pub fn parse(text: &str) -> Option<(Color, ColorFormat)> {
+    let text = text.trim().to_ascii_lowercase();
+    if let Some(hex) = text.strip_prefix('#') {
+        return parse_hex(hex).map(|color| (color, ColorFormat::Hex));
+    }
+
+    let (function, arguments) = text.strip_suffix(')')?.split_once('(')?;
+    let (components, alpha) = components(arguments)?;
+    let alpha = match alpha {
+        Some(alpha) => number_or_percentage(alpha, 1.0)?,
+        None => 1.0,
+    };
+    let color = match (function.trim(), components.as_slice()) {
+        ("rgb" | "rgba", [r, g, b]) => rgb(
+            number_or_percentage(r, 255.0)? / 255.0,
+            number_or_percentage(g, 255.0)? / 255.0,
+            number_or_percentage(b, 255.0)? / 255.0,
+        ),
+        ("hsl" | "hsla", [h, s, l]) => hsl_to_rgb(
+            hue(h)?,
+            number_or_percentage(s, 100.0)? / 100.0,
+            number_or_percentage(l, 100.0)? / 100.0,
+        ),
+        ("oklch", [l, c, h]) => oklch_to_rgb(
+            number_or_percentage(l, 1.0)?,
+            number_or_percentage(c, 0.4)?,
+            hue(h)?,
+        ),
+        _ => return None,
+    };
+    let format = match function.trim() {
+        "rgb" | "rgba" => ColorFormat::Rgb,
+        "hsl" | "hsla" => ColorFormat::Hsl,
+        _ => ColorFormat::Oklch,
+    };
+    Some((
+        Color {
+            a: alpha.clamp(0.0, 1.0),
+            ..color
+        },
+        format,
+    ))
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |index: usize| u8::from_str_radix(&hex[index..=index], 16).ok();
+    let byte = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    let [r, g, b, a] = match hex.len() {
+        3 | 4 => {
+            let mut channels = [255; 4];
+            for (index, channel) in channels.iter_mut().take(hex.len()).enumerate() {
+                *channel = digit(index)? * 17;
+            }
+            channels
+        }
+        6 | 8 => {
+            let mut channels = [255; 4];
+            for (index, channel) in channels.iter_mut().take(hex.len() / 2).enumerate() {
+                *channel = byte(index * 2)?;
+            }
+            channels
+        }
+        _ => return None,
+    };
+    Some(Color::from_rgba8(r, g, b, a as f32 / 255.0))
+}
+
+/// Splits the three components from the optional alpha, given after a `/` or as a fourth
+/// comma separated component
+fn components(arguments: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let (components, alpha) = match arguments.split_once('/') {
+        Some((components, alpha)) => (components, Some(alpha.trim())),
+        None => (arguments, None),
+    };
+    let mut components = components
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>();
+    match (components.len(), alpha) {
+        (3, _) => Some((components, alpha)),
+        (4, None) => {
+            let alpha = components.pop();
+            Some((components, alpha))
+        }
+        _ => None,
+    }
+}
+
+/// Percentages are relative to `full`
+fn number_or_percentage(value: &str, full: f32) -> Option<f32> {
+    match value.strip_suffix('%') {
+        Some(percentage) => Some(percentage.parse::<f32>().ok()? / 100.0 * full),
+        None => value.parse().ok(),
+    }
+}
+
+/// Hue in degrees, from a bare number or with a `deg` or `turn` unit
+fn hue(value: &str) -> Option<f32> {
+    let degrees = if let Some(degrees) = value.strip_suffix("deg") {
+        degrees.parse().ok()?
+    } else if let Some(turns) = value.strip_suffix("turn") {
+        turns.parse::<f32>().ok()? * 360.0
+    } else {
+        value.parse().ok()?
+    };
+    Some(degrees.rem_euclid(360.0))
+}
+
+fn round(value: f32, decimals: i32) -> f32 {
+    let factor = 10f32.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// Clamped since iced expects channels between 0 and 1
+fn rgb(r: f32, g: f32, b: f32) -> Color {
+    Color::from_rgb(r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0))
+}
+
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> Color {
+    let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = chroma * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = l - chroma / 2.0;
+    rgb(r + m, g + m, b + m)
+}
+
+fn rgb_to_hsl(color: Color) -> (f32, f32, f32) {
+    let Color { r, g, b, .. } = color;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, l);
+    }
+    let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (h, s, l)
+}
+
+fn to_linear(channel: f32) -> f32 {
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(channel: f32) -> f32 {
+    let channel = if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    };
+    channel.clamp(0.0, 1.0)
+}
+
+/// Out of gamut colors are clamped to sRGB
+fn oklch_to_rgb(l: f32, c: f32, h: f32) -> Color {
+    let (a, b) = (c * h.to_radians().cos(), c * h.to_radians().sin());
+    let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m_ = (l - 0.105_561_35 * a - 0.063_854_17 * b).powi(3);
+    let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+    rgb(
+        from_linear(4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_),
+        from_linear(-1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_),
+        from_linear(-0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_),
+    )
+}
+
+fn rgb_to_oklch(color: Color) -> (f32, f32, f32) {
+    let (r, g, b) = (to_linear(color.r), to_linear(color.g), to_linear(color.b));
+    let l_ = (0.412_221_46 * r + 0.536_315_3 * g + 0.051_463_32 * b).cbrt();
+    let m_ = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s_ = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    let l = 0.210_454_26 * l_ + 0.793_617_8 * m_ - 0.004_072_047 * s_;
+    let a = 1.977_998_5 * l_ - 2.428_592_2 * m_ + 0.450_593_7 * s_;
+    let b = 0.025_904_037 * l_ + 0.782_771_77 * m_ - 0.808_675_77 * s_;
+    let c = a.hypot(b);
+    // Grays have no meaningful hue
+    let h = if c < 1e-4 {
+        0.0
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    };
+    (l, c, h)
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hooks::CaptureHook,
+    hotkey::{GlobalShortcut, ModifierSet},
+    input::{InputBackendKind, KeyboardLayout},
+    paste::{PasteRule, PasteSuffix, YankPopReplace, DEFAULT_PASTE_CHORD},
+    plugin::PluginSettings,
+    smart_action::{ActionKey, SmartAction},
+    transform::Pipeline,
+    utils::project_dirs,
+};
+
+const CONFIG_NAME: &str = "config.toml";
+
+/// What happens to a history entry when it is pasted again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RepasteBehavior {
+    /// Moves the entry back to the top of the history
+    #[default]
+    Bump,
+    KeepInPlace,
+}
+
+impl RepasteBehavior {
+    pub const ALL: [RepasteBehavior; 2] = [RepasteBehavior::Bump, RepasteBehavior::KeepInPlace];
+}
+
+impl Display for RepasteBehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RepasteBehavior::Bump => "Move to top",
+            RepasteBehavior::KeepInPlace => "Keep in place",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub input_backend: InputBackendKind,
+    pub paste_chord: Vec<rdev::Key>,
+    pub paste_rules: Vec<PasteRule>,
+    pub keyboard_layout: KeyboardLayout,
+    pub type_out_delay_ms: u64,
+    /// Puts back what was on the clipboard before pasting a history entry
+    pub restore_clipboard: bool,
+    pub restore_clipboard_delay_ms: u64,
+    pub repaste_behavior: RepasteBehavior,
+    /// Pastes the Nth most recent entry on modifiers + digit N
+    pub quick_paste_enabled: bool,
+    pub quick_paste_modifiers: ModifierSet,
+    /// Pastes the latest entry, pressing again replaces it with the previous one
+    pub yank_pop_shortcut: Option<GlobalShortcut>,
+    pub yank_pop_replace: YankPopReplace,
+    /// Pastes the next entry of the queue built in the history window
+    pub queue_paste_shortcut: Option<GlobalShortcut>,
+    pub queue_paste_suffix: PasteSuffix,
+    /// Toggles collect mode, where copies are appended to a single entry
+    pub collect_shortcut: Option<GlobalShortcut>,
+    pub collect_separator: String,
+    /// Placed between entries when merging or pasting several at once
+    pub join_separator: String,
+    /// Trashed entries older than this are purged, 0 keeps them forever
+    pub trash_retention_days: u64,
+    pub pipelines: Vec<Pipeline>,
+    /// Keyed by plugin file name, plugins missing from it are disabled
+    pub plugins: BTreeMap<String, PluginSettings>,
+    pub capture_hooks: Vec<CaptureHook>,
+    /// Runs the first action bound to the key applicable to the entry under the cursor
+    pub smart_action_keys: Vec<ActionKey>,
+    /// Widens the history window to preview the selected entry next to the list
+    pub preview_pane: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            input_backend: Default::default(),
+            paste_chord: DEFAULT_PASTE_CHORD.to_vec(),
+            paste_rules: Vec::new(),
+            keyboard_layout: Default::default(),
+            type_out_delay_ms: 10,
+            restore_clipboard: false,
+            restore_clipboard_delay_ms: 300,
+            repaste_behavior: Default::default(),
+            quick_paste_enabled: false,
+            quick_paste_modifiers: ModifierSet {
+                ctrl: true,
+                alt: true,
+                ..Default::default()
+            },
+            yank_pop_shortcut: None,
+            yank_pop_replace: Default::default(),
+            queue_paste_shortcut: None,
+            queue_paste_suffix: Default::default(),
+            collect_shortcut: None,
+            collect_separator: "\n".into(),
+            join_separator: "\n".into(),
+            trash_retention_days: 30,
+            pipelines: Vec::new(),
+            plugins: BTreeMap::new(),
+            capture_hooks: Vec::new(),
+            smart_action_keys: SmartAction::default_keys(),
+            preview_pane: false,
+        }
+    }
+}
+
+fn config_path() -> anyhow::Result<PathBuf> {
+    let mut config_dir = project_dirs()?.config_dir().to_path_buf();
+    if !config_dir.try_exists()? {
+        fs::create_dir_all(&config_dir)?;
+    }
+    config_dir.push(CONFIG_NAME);
+    Ok(config_dir)
+}
+
+pub fn load() -> anyhow::Result<Config> {
+    let path = config_path()?;
+    if !path.try_exists()? {
+        return Ok(Config::default());
+    }
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub async fn save(config: Config) -> anyhow::Result<()> {
+    tokio::fs::write(config_path()?, toml::to_string_pretty(&config)?).await?;
+    Ok(())
+}
@@ -1,18 +1,41 @@
 use std::fs;
 
-use directories::ProjectDirs;
 use migration::{Migrator, MigratorTrait};
 use sea_orm::{Database, DatabaseConnection};
 use tracing::info;
 
-use crate::{APPLICATION, ORGANIZATION, QUALIFIER};
+use crate::{classify::ContentKind, clipboard::ClipboardContent, utils::project_dirs};
 
 const DB_NAME: &str = "clippy.sqlite";
 
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub entry: entity::entry::Model,
+    /// Representations besides the plain text stored in `entry.data`
+    pub formats: Vec<ClipboardContent>,
+    pub tags: Vec<String>,
+}
+
+impl Item {
+    pub fn contents(&self, plain_text: bool) -> Vec<ClipboardContent> {
+        let mut contents = vec![ClipboardContent::Text(self.entry.data.clone())];
+        if !plain_text {
+            contents.extend(self.formats.iter().cloned());
+        }
+        contents
+    }
+
+    pub fn kinds(&self) -> Vec<ContentKind> {
+        self.entry
+            .kinds
+            .as_deref()
+            .map(ContentKind::parse_list)
+            .unwrap_or_default()
+    }
+}
+
 pub async fn get_db() -> anyhow::Result<DatabaseConnection> {
-    let dirs = ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION)
-        .ok_or(anyhow::anyhow!("Could not get os dirs"))?;
-    let mut data_dir = dirs.data_dir().to_path_buf();
+    let mut data_dir = project_dirs()?.data_dir().to_path_buf();
     if !data_dir.try_exists()? {
         fs::create_dir_all(&data_dir)?;
     }
@@ -27,34 +50,569 @@ pub async fn get_db() -> anyhow::Result<DatabaseConnection> {
 }
 
 pub mod repo {
-    use chrono::Local;
-    use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, Set};
+    use std::collections::{HashMap, HashSet};
+
+    use chrono::{Local, NaiveDateTime};
+    use sea_orm::{
+        sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction,
+        EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect, Select, Set,
+        TransactionTrait,
+    };
+
+    use super::Item;
+    use crate::{classify, clipboard::ClipboardContent};
+
+    const HTML_FORMAT: &str = "html";
+    const RTF_FORMAT: &str = "rtf";
+    const FILES_FORMAT: &str = "files";
+
+    fn encode_format(content: ClipboardContent) -> Option<(&'static str, String)> {
+        match content {
+            ClipboardContent::Text(_) => None,
+            ClipboardContent::Html(html) => Some((HTML_FORMAT, html)),
+            ClipboardContent::Rtf(rtf) => Some((RTF_FORMAT, rtf)),
+            ClipboardContent::Files(files) => Some((FILES_FORMAT, files.join("\n"))),
+        }
+    }
+
+    /// Sets the entry text along with the kinds of content detected in it
+    fn set_data(entry: &mut entity::entry::ActiveModel, data: String) {
+        let classification = classify::classify(&data);
+        entry.kinds = Set(Some(classification.kinds_string()));
+        entry.language = Set(classification.language.map(str::to_owned));
+        entry.data = Set(data);
+    }
+
+    fn decode_format(format: entity::entry_format::Model) -> Option<ClipboardContent> {
+        match format.format.as_str() {
+            HTML_FORMAT => Some(ClipboardContent::Html(format.data)),
+            RTF_FORMAT => Some(ClipboardContent::Rtf(format.data)),
+            FILES_FORMAT => Some(ClipboardContent::Files(
+                format.data.lines().map(str::to_owned).collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// `formats` holds the other representations of the copied item, text ones are ignored.
+    /// `source_app` is the class of the window the item was copied from.
+    pub async fn add_item(
+        db: &DatabaseConnection,
+        data: String,
+        formats: Vec<ClipboardContent>,
+        tags: Vec<String>,
+        source_app: Option<String>,
+        added_at: NaiveDateTime,
+    ) -> anyhow::Result<i32> {
+        let txn = db.begin().await?;
+        let mut entry = entity::entry::ActiveModel {
+            added_at: Set(added_at),
+            last_used_at: Set(Some(added_at)),
+            source_app: Set(source_app),
+            ..Default::default()
+        };
+        set_data(&mut entry, data);
+        let entry_id = entity::entry::Entity::insert(entry)
+            .exec(&txn)
+            .await?
+            .last_insert_id;
+
+        let formats = formats
+            .into_iter()
+            .filter_map(encode_format)
+            .map(|(format, data)| entity::entry_format::ActiveModel {
+                entry_id: Set(entry_id),
+                format: Set(format.to_owned()),
+                data: Set(data),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        if !formats.is_empty() {
+            entity::entry_format::Entity::insert_many(formats)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        let tags = tags.into_iter().collect::<HashSet<_>>();
+        if !tags.is_empty() {
+            entity::entry_tag::Entity::insert_many(tags.into_iter().map(|tag| {
+                entity::entry_tag::ActiveModel {
+                    entry_id: Set(entry_id),
+                    tag: Set(tag),
+                    ..Default::default()
+                }
+            }))
+            .exec_without_returning(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+        Ok(entry_id)
+    }
+
+    /// Appends `data` to the collecting entry, or starts a new one when there is none yet.
+    /// Returns the id of the collecting entry.
+    pub async fn collect(
+        db: &DatabaseConnection,
+        entry_id: Option<i32>,
+        data: String,
+        separator: &str,
+    ) -> anyhow::Result<i32> {
+        let txn = db.begin().await?;
+        let entry = match entry_id {
+            Some(entry_id) => {
+                entity::entry::Entity::find_by_id(entry_id)
+                    .filter(entity::entry::Column::DeletedAt.is_null())
+                    .one(&txn)
+                    .await?
+            }
+            None => None,
+        };
+        let entry_id = match entry {
+            Some(entry) => {
+                let mut collected = entry.data.clone();
+                collected.push_str(separator);
+                collected.push_str(&data);
+                let mut entry: entity::entry::ActiveModel = entry.into();
+                set_data(&mut entry, collected);
+                entry.last_used_at = Set(Some(Local::now().naive_local()));
+                entry.update(&txn).await?.id
+            }
+            // The collecting entry only keeps text, other formats can't be joined
+            None => {
+                let now = Local::now().naive_local();
+                let mut entry = entity::entry::ActiveModel {
+                    added_at: Set(now),
+                    last_used_at: Set(Some(now)),
+                    ..Default::default()
+                };
+                set_data(&mut entry, data);
+                entity::entry::Entity::insert(entry)
+                    .exec(&txn)
+                    .await?
+                    .last_insert_id
+            }
+        };
+        txn.commit().await?;
+        Ok(entry_id)
+    }
+
+    async fn find_items(
+        db: &DatabaseConnection,
+        query: Select<entity::entry::Entity>,
+    ) -> anyhow::Result<Vec<Item>> {
+        let mut tags = HashMap::<i32, Vec<String>>::new();
+        for entry_tag in entity::entry_tag::Entity::find()
+            .order_by_asc(entity::entry_tag::Column::Tag)
+            .all(db)
+            .await?
+        {
+            tags.entry(entry_tag.entry_id)
+                .or_default()
+                .push(entry_tag.tag);
+        }
+
+        Ok(query
+            .find_with_related(entity::entry_format::Entity)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(entry, formats)| Item {
+                tags: tags.remove(&entry.id).unwrap_or_default(),
+                entry,
+                formats: formats.into_iter().filter_map(decode_format).collect(),
+            })
+            .collect())
+    }
+
+    /// Pinned entries come first, then the most recent ones
+    pub async fn get_items(db: &DatabaseConnection) -> anyhow::Result<Vec<Item>> {
+        find_items(
+            db,
+            entity::entry::Entity::find()
+                .filter(entity::entry::Column::DeletedAt.is_null())
+                .order_by_desc(entity::entry::Column::Pinned)
+                .order_by_desc(entity::entry::Column::LastUsedAt),
+        )
+        .await
+    }
+
+    /// Most recently deleted first
+    pub async fn get_trashed_items(db: &DatabaseConnection) -> anyhow::Result<Vec<Item>> {
+        find_items(
+            db,
+            entity::entry::Entity::find()
+                .filter(entity::entry::Column::DeletedAt.is_not_null())
+                .order_by_desc(entity::entry::Column::DeletedAt),
+        )
+        .await
+    }
+
+    /// `index` 0 is the most recent entry
+    pub async fn get_item_at(db: &DatabaseConnection, index: u64) -> anyhow::Result<Option<Item>> {
+        let Some(entry) = entity::entry::Entity::find()
+            .filter(entity::entry::Column::DeletedAt.is_null())
+            .order_by_desc(entity::entry::Column::LastUsedAt)
+            .offset(index)
+            .one(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let formats = entry
+            .find_related(entity::entry_format::Entity)
+            .all(db)
+            .await?;
+        let tags = entry
+            .find_related(entity::entry_tag::Entity)
+            .order_by_asc(entity::entry_tag::Column::Tag)
+            .all(db)
+            .await?;
+        Ok(Some(Item {
+            entry,
+            formats: formats.into_iter().filter_map(decode_format).collect(),
+            tags: tags.into_iter().map(|entry_tag| entry_tag.tag).collect(),
+        }))
+    }
+
+    /// Moves the entry back to the top of the history, it keeps the time it was added at
+    pub async fn bump(db: &DatabaseConnection, entry: &entity::entry::Model) -> anyhow::Result<()> {
+        entity::entry::ActiveModel {
+            id: Set(entry.id),
+            last_used_at: Set(Some(Local::now().naive_local())),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Counts one more paste of the entry
+    pub async fn record_use(db: &DatabaseConnection, id: i32) -> anyhow::Result<()> {
+        entity::entry::Entity::update_many()
+            .col_expr(
+                entity::entry::Column::UseCount,
+                Expr::col(entity::entry::Column::UseCount).add(1),
+            )
+            .filter(entity::entry::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces the entry text, the previous one is kept as a revision.
+    /// Other formats are dropped since they no longer match the text.
+    pub async fn edit_item(db: &DatabaseConnection, id: i32, data: String) -> anyhow::Result<()> {
+        let txn = db.begin().await?;
+        let Some(entry) = entity::entry::Entity::find_by_id(id).one(&txn).await? else {
+            anyhow::bail!("Entry {id} does not exist");
+        };
+        record_revision(&txn, &entry).await?;
+        replace_data(&txn, entry, data).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Replaces the entry text with the output of a capture hook. No revision is kept, the
+    /// copied text was never edited by the user.
+    pub async fn replace_with_hook_output(
+        db: &DatabaseConnection,
+        id: i32,
+        data: String,
+    ) -> anyhow::Result<()> {
+        let txn = db.begin().await?;
+        let Some(entry) = entity::entry::Entity::find_by_id(id).one(&txn).await? else {
+            anyhow::bail!("Entry {id} does not exist");
+        };
+        replace_data(&txn, entry, data).await?;
+        txn.commit().await?;
+        Ok(())
+    }
 
-    pub async fn add_item(db: &DatabaseConnection, data: String) -> anyhow::Result<()> {
-        entity::entry::Entity::insert(entity::entry::ActiveModel {
-            data: Set(data),
-            added_at: Set(Local::now().naive_local()),
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SyncOutcome {
+        Updated,
+        Unchanged,
+        /// The entry changed or was deleted while being edited, the edit became a new entry
+        AddedAsNew,
+    }
+
+    /// Stores an edit made outside of the app, `original` is the text the edit started from
+    pub async fn sync_edit(
+        db: &DatabaseConnection,
+        id: i32,
+        original: &str,
+        data: String,
+    ) -> anyhow::Result<SyncOutcome> {
+        if data == original {
+            return Ok(SyncOutcome::Unchanged);
+        }
+        let txn = db.begin().await?;
+        let entry = entity::entry::Entity::find_by_id(id)
+            .filter(entity::entry::Column::DeletedAt.is_null())
+            .one(&txn)
+            .await?;
+        let outcome = match entry {
+            Some(entry) if entry.data == original => {
+                record_revision(&txn, &entry).await?;
+                replace_data(&txn, entry, data).await?;
+                SyncOutcome::Updated
+            }
+            _ => {
+                let now = Local::now().naive_local();
+                let mut entry = entity::entry::ActiveModel {
+                    added_at: Set(now),
+                    last_used_at: Set(Some(now)),
+                    ..Default::default()
+                };
+                set_data(&mut entry, data);
+                entity::entry::Entity::insert(entry).exec(&txn).await?;
+                SyncOutcome::AddedAsNew
+            }
+        };
+        txn.commit().await?;
+        Ok(outcome)
+    }
+
+    /// Keeps the current text of the entry as a revision
+    async fn record_revision(
+        txn: &DatabaseTransaction,
+        entry: &entity::entry::Model,
+    ) -> anyhow::Result<()> {
+        entity::entry_revision::Entity::insert(entity::entry_revision::ActiveModel {
+            entry_id: Set(entry.id),
+            data: Set(entry.data.clone()),
+            edited_at: Set(Local::now().naive_local()),
             ..Default::default()
         })
-        .exec_without_returning(db)
+        .exec(txn)
         .await?;
         Ok(())
     }
 
-    pub async fn get_items(db: &DatabaseConnection) -> anyhow::Result<Vec<entity::entry::Model>> {
-        Ok(entity::entry::Entity::find()
-            .order_by_desc(entity::entry::Column::AddedAt)
+    /// Other formats are dropped since they no longer match the new text
+    async fn replace_data(
+        txn: &DatabaseTransaction,
+        entry: entity::entry::Model,
+        data: String,
+    ) -> anyhow::Result<()> {
+        entity::entry_format::Entity::delete_many()
+            .filter(entity::entry_format::Column::EntryId.eq(entry.id))
+            .exec(txn)
+            .await?;
+        let mut entry: entity::entry::ActiveModel = entry.into();
+        set_data(&mut entry, data);
+        entry.update(txn).await?;
+        Ok(())
+    }
+
+    /// Classifies the entries stored before classification existed
+    pub async fn classify_entries(db: &DatabaseConnection) -> anyhow::Result<()> {
+        let entries = entity::entry::Entity::find()
+            .filter(entity::entry::Column::Kinds.is_null())
+            .all(db)
+            .await?;
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let txn = db.begin().await?;
+        for entry in entries {
+            let data = entry.data.clone();
+            let mut entry: entity::entry::ActiveModel = entry.into();
+            set_data(&mut entry, data);
+            entry.update(&txn).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Most recent first
+    pub async fn get_revisions(
+        db: &DatabaseConnection,
+        id: i32,
+    ) -> anyhow::Result<Vec<entity::entry_revision::Model>> {
+        Ok(entity::entry_revision::Entity::find()
+            .filter(entity::entry_revision::Column::EntryId.eq(id))
+            .order_by_desc(entity::entry_revision::Column::EditedAt)
             .all(db)
             .await?)
     }
 
-    pub async fn delete(
+    async fn delete_entries(txn: &DatabaseTransaction, ids: &[i32]) -> anyhow::Result<()> {
+        entity::entry_format::Entity::delete_many()
+            .filter(entity::entry_format::Column::EntryId.is_in(ids.iter().copied()))
+            .exec(txn)
+            .await?;
+        entity::entry_revision::Entity::delete_many()
+            .filter(entity::entry_revision::Column::EntryId.is_in(ids.iter().copied()))
+            .exec(txn)
+            .await?;
+        entity::entry_tag::Entity::delete_many()
+            .filter(entity::entry_tag::Column::EntryId.is_in(ids.iter().copied()))
+            .exec(txn)
+            .await?;
+        entity::entry::Entity::delete_many()
+            .filter(entity::entry::Column::Id.is_in(ids.iter().copied()))
+            .exec(txn)
+            .await?;
+        Ok(())
+    }
+
+    /// Moves the entries to the trash, they stay there until restored or purged
+    pub async fn delete_items(db: &DatabaseConnection, ids: &[i32]) -> anyhow::Result<()> {
+        set_deleted_at(db, ids, Some(Local::now().naive_local())).await
+    }
+
+    pub async fn restore_items(db: &DatabaseConnection, ids: &[i32]) -> anyhow::Result<()> {
+        set_deleted_at(db, ids, None).await
+    }
+
+    async fn set_deleted_at(
         db: &DatabaseConnection,
-        entry: &entity::entry::Model,
+        ids: &[i32],
+        deleted_at: Option<NaiveDateTime>,
     ) -> anyhow::Result<()> {
-        entity::entry::Entity::delete_by_id(entry.id)
-            .exec(db)
+        let txn = db.begin().await?;
+        update_deleted_at(&txn, ids, deleted_at).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn update_deleted_at(
+        txn: &DatabaseTransaction,
+        ids: &[i32],
+        deleted_at: Option<NaiveDateTime>,
+    ) -> anyhow::Result<()> {
+        entity::entry::Entity::update_many()
+            .col_expr(entity::entry::Column::DeletedAt, Expr::value(deleted_at))
+            .filter(entity::entry::Column::Id.is_in(ids.iter().copied()))
+            .exec(txn)
+            .await?;
+        Ok(())
+    }
+
+    /// Permanently removes trashed entries deleted before the given time, or all of them
+    pub async fn purge_trash(
+        db: &DatabaseConnection,
+        deleted_before: Option<NaiveDateTime>,
+    ) -> anyhow::Result<()> {
+        let txn = db.begin().await?;
+        let mut query = entity::entry::Entity::find()
+            .select_only()
+            .column(entity::entry::Column::Id)
+            .filter(entity::entry::Column::DeletedAt.is_not_null());
+        if let Some(deleted_before) = deleted_before {
+            query = query.filter(entity::entry::Column::DeletedAt.lt(deleted_before));
+        }
+        let ids = query.into_tuple::<i32>().all(&txn).await?;
+        if !ids.is_empty() {
+            delete_entries(&txn, &ids).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_pinned(
+        db: &DatabaseConnection,
+        ids: &[i32],
+        pinned: bool,
+    ) -> anyhow::Result<()> {
+        let txn = db.begin().await?;
+        entity::entry::Entity::update_many()
+            .col_expr(entity::entry::Column::Pinned, Expr::value(pinned))
+            .filter(entity::entry::Column::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
             .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Entries already holding the tag are left untouched
+    pub async fn add_tag(db: &DatabaseConnection, ids: &[i32], tag: &str) -> anyhow::Result<()> {
+        let txn = db.begin().await?;
+        let tagged = entity::entry_tag::Entity::find()
+            .filter(entity::entry_tag::Column::Tag.eq(tag))
+            .filter(entity::entry_tag::Column::EntryId.is_in(ids.iter().copied()))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|entry_tag| entry_tag.entry_id)
+            .collect::<HashSet<_>>();
+        let entry_tags = ids
+            .iter()
+            .filter(|id| !tagged.contains(id))
+            .map(|id| entity::entry_tag::ActiveModel {
+                entry_id: Set(*id),
+                tag: Set(tag.to_owned()),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        if !entry_tags.is_empty() {
+            entity::entry_tag::Entity::insert_many(entry_tags)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Replaces the entries with a single one joining their text in the given order, the
+    /// originals are moved to the trash.
+    /// The merged entry keeps every tag and stays pinned if one of them was.
+    pub async fn merge_items(
+        db: &DatabaseConnection,
+        ids: &[i32],
+        separator: &str,
+    ) -> anyhow::Result<()> {
+        let txn = db.begin().await?;
+        let entries = entity::entry::Entity::find()
+            .filter(entity::entry::Column::Id.is_in(ids.iter().copied()))
+            .all(&txn)
+            .await?;
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let data = ids
+            .iter()
+            .filter_map(|id| entries.iter().find(|entry| entry.id == *id))
+            .map(|entry| entry.data.as_str())
+            .collect::<Vec<_>>()
+            .join(separator);
+        let tags = entity::entry_tag::Entity::find()
+            .filter(entity::entry_tag::Column::EntryId.is_in(ids.iter().copied()))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|entry_tag| entry_tag.tag)
+            .collect::<HashSet<_>>();
+
+        let now = Local::now().naive_local();
+        let mut entry = entity::entry::ActiveModel {
+            added_at: Set(now),
+            last_used_at: Set(Some(now)),
+            pinned: Set(entries.iter().any(|entry| entry.pinned)),
+            ..Default::default()
+        };
+        set_data(&mut entry, data);
+        let entry_id = entity::entry::Entity::insert(entry)
+            .exec(&txn)
+            .await?
+            .last_insert_id;
+        if !tags.is_empty() {
+            entity::entry_tag::Entity::insert_many(tags.into_iter().map(|tag| {
+                entity::entry_tag::ActiveModel {
+                    entry_id: Set(entry_id),
+                    tag: Set(tag),
+                    ..Default::default()
+                }
+            }))
+            .exec_without_returning(&txn)
+            .await?;
+        }
+
+        update_deleted_at(&txn, ids, Some(Local::now().naive_local())).await?;
+
+        txn.commit().await?;
         Ok(())
     }
 }
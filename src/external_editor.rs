@@ -0,0 +1,105 @@
+use std::{
+    env,
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use tempfile::NamedTempFile;
+use tokio::{fs, process::Command};
+
+use crate::utils::project_dirs;
+
+/// Editors drawing in a terminal, they cannot run without one
+const TERMINAL_EDITORS: [&str; 9] = [
+    "vi", "vim", "nvim", "nano", "pico", "micro", "hx", "helix", "kak",
+];
+
+/// Editor command line from `$VISUAL`, falling back to `$EDITOR`.
+/// GUI editors have to be told to wait for the file to be closed, like `code --wait`.
+fn editor_command() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+}
+
+fn is_terminal_editor(editor: &str) -> bool {
+    let program = editor.split_whitespace().next().unwrap_or_default();
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    TERMINAL_EDITORS.contains(&name)
+}
+
+/// Private to the user: the runtime dir when there is one, the cache dir otherwise
+fn temp_dir() -> anyhow::Result<PathBuf> {
+    let dir = match env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir),
+        None => project_dirs()?.cache_dir().to_path_buf(),
+    };
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Created exclusively with owner-only permissions, removed when dropped
+fn temp_file(entry_id: i32, text: &str) -> anyhow::Result<NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("joy-clippy-entry-{entry_id}-"))
+        .suffix(".txt")
+        .tempfile_in(temp_dir()?)?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+    Ok(file)
+}
+
+/// Runs the command through the shell so editors configured with arguments work
+#[cfg(unix)]
+fn shell_command(editor: &str, path: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(path);
+    command
+}
+
+#[cfg(windows)]
+fn shell_command(editor: &str, path: &Path) -> Command {
+    let mut command = Command::new("cmd");
+    command
+        .arg("/C")
+        .arg(format!("{editor} \"{}\"", path.display()));
+    command
+}
+
+/// Writes the text to a temporary file, opens it in the user editor and returns the saved
+/// content once the editor exits.
+pub async fn edit(entry_id: i32, text: &str) -> anyhow::Result<String> {
+    let Some(editor) = editor_command() else {
+        anyhow::bail!("Neither $VISUAL nor $EDITOR is set");
+    };
+    // Started from a desktop session there is no terminal to draw in
+    if is_terminal_editor(&editor) && !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "{editor} needs a terminal, set $VISUAL to a graphical editor such as `code --wait`"
+        );
+    }
+    // Removed on drop, whichever way this returns
+    let file = temp_file(entry_id, text)?;
+
+    let status = shell_command(&editor, file.path()).status().await?;
+    if !status.success() {
+        anyhow::bail!("{editor} exited with {status}");
+    }
+    let mut edited = fs::read_to_string(file.path()).await?;
+    // Most editors end the file with a line break on save
+    if edited.ends_with('\n') && !text.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    Ok(edited)
+}
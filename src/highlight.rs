@@ -0,0 +1,67 @@
+//! Syntax highlighting of code entries, with the grammars bundled in iced so it works offline
+
+use iced::{
+    advanced::text::Highlighter as _,
+    highlighter::{self, Highlighter},
+    Color,
+};
+
+/// Colors of the highlighting theme, code is drawn on its background whatever the app theme
+pub const BACKGROUND: Color = Color::from_rgb8(0x2b, 0x30, 0x3b);
+pub const FOREGROUND: Color = Color::from_rgb8(0xc0, 0xc5, 0xce);
+pub const LINE_NUMBER: Color = Color::from_rgb8(0x65, 0x73, 0x7e);
+
+/// Longer entries are cut, the preview is not meant to read whole files
+const MAX_LINES: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub text: String,
+    pub color: Option<Color>,
+}
+
+/// Grammar of a language detected by the classifier, typescript has none bundled
+fn token(language: &str) -> &str {
+    match language {
+        "rust" => "rs",
+        "python" => "py",
+        "javascript" | "typescript" => "js",
+        "shell" => "sh",
+        language => language,
+    }
+}
+
+/// Spans of each line, unknown languages are left plain
+pub fn highlight(text: &str, language: &str) -> Vec<Vec<Span>> {
+    let mut highlighter = Highlighter::new(&highlighter::Settings {
+        theme: highlighter::Theme::Base16Ocean,
+        token: token(language).to_owned(),
+    });
+    text.lines()
+        .take(MAX_LINES)
+        .map(|line| {
+            let mut spans = Vec::new();
+            let mut end = 0;
+            for (range, highlight) in highlighter.highlight_line(line) {
+                if range.start > end {
+                    spans.push(Span {
+                        text: line[end..range.start].to_owned(),
+                        color: None,
+                    });
+                }
+                spans.push(Span {
+                    text: line[range.clone()].to_owned(),
+                    color: highlight.to_format().color,
+                });
+                end = range.end;
+            }
+            if end < line.len() {
+                spans.push(Span {
+                    text: line[end..].to_owned(),
+                    color: None,
+                });
+            }
+            spans
+        })
+        .collect()
+}
@@ -0,0 +1,271 @@
+use std::{
+    fmt::{self, Display},
+    process::Stdio,
+    time::Duration,
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::{io::AsyncWriteExt, process::Command, time::timeout};
+
+use crate::{classify::ContentKind, clipboard::ClipboardFormat};
+
+/// Format an entry must have, besides text, for a hook to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FormatFilter {
+    #[default]
+    Any,
+    Html,
+    Rtf,
+    Files,
+}
+
+impl FormatFilter {
+    pub const ALL: [FormatFilter; 4] = [
+        FormatFilter::Any,
+        FormatFilter::Html,
+        FormatFilter::Rtf,
+        FormatFilter::Files,
+    ];
+
+    fn matches(self, formats: &[ClipboardFormat]) -> bool {
+        let required = match self {
+            FormatFilter::Any => return true,
+            FormatFilter::Html => ClipboardFormat::Html,
+            FormatFilter::Rtf => ClipboardFormat::Rtf,
+            FormatFilter::Files => ClipboardFormat::Files,
+        };
+        formats.contains(&required)
+    }
+}
+
+impl Display for FormatFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FormatFilter::Any => "Any format",
+            FormatFilter::Html => "With HTML",
+            FormatFilter::Rtf => "With RTF",
+            FormatFilter::Files => "With files",
+        })
+    }
+}
+
+/// Kind of content an entry must be classified as for a hook to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KindFilter {
+    #[default]
+    Any,
+    Kind(ContentKind),
+}
+
+impl KindFilter {
+    pub const ALL: [KindFilter; 9] = [
+        KindFilter::Any,
+        KindFilter::Kind(ContentKind::Url),
+        KindFilter::Kind(ContentKind::Email),
+        KindFilter::Kind(ContentKind::Path),
+        KindFilter::Kind(ContentKind::Json),
+        KindFilter::Kind(ContentKind::Code),
+        KindFilter::Kind(ContentKind::Color),
+        KindFilter::Kind(ContentKind::Number),
+        KindFilter::Kind(ContentKind::Markdown),
+    ];
+
+    fn matches(self, kinds: &[ContentKind]) -> bool {
+        match self {
+            KindFilter::Any => true,
+            KindFilter::Kind(kind) => kinds.contains(&kind),
+        }
+    }
+}
+
+impl Display for KindFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KindFilter::Any => f.write_str("Any content"),
+            KindFilter::Kind(kind) => write!(f, "Is {kind}"),
+        }
+    }
+}
+
+/// Regex the text of an entry must match, compiled once when it is set.
+/// An invalid pattern never matches, its error is shown in the settings window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct HookPattern {
+    source: String,
+    /// `None` for an empty pattern, which matches everything
+    regex: Result<Option<Regex>, regex::Error>,
+}
+
+impl HookPattern {
+    pub fn new(source: String) -> Self {
+        let regex = if source.is_empty() {
+            Ok(None)
+        } else {
+            Regex::new(&source).map(Some)
+        };
+        Self { source, regex }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn error(&self) -> Option<&regex::Error> {
+        self.regex.as_ref().err()
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        match &self.regex {
+            Ok(Some(regex)) => regex.is_match(text),
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Used when loading the config
+impl From<String> for HookPattern {
+    fn from(source: String) -> Self {
+        let pattern = HookPattern::new(source);
+        if let Some(e) = pattern.error() {
+            tracing::error!("Invalid capture hook pattern {}\n{e:?}", pattern.source);
+        }
+        pattern
+    }
+}
+
+impl From<HookPattern> for String {
+    fn from(pattern: HookPattern) -> Self {
+        pattern.source
+    }
+}
+
+impl Default for HookPattern {
+    fn default() -> Self {
+        HookPattern::new(String::new())
+    }
+}
+
+impl PartialEq for HookPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for HookPattern {}
+
+/// Shell command run on every new entry matching its filters, the text is written to its
+/// stdin and the metadata passed in `JOY_CLIPPY_*` env vars
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureHook {
+    pub command: String,
+    /// Empty matches everything
+    pub pattern: HookPattern,
+    pub format: FormatFilter,
+    pub kind: KindFilter,
+    /// Replaces the entry text with the command output
+    pub replace_entry: bool,
+    pub timeout_ms: u64,
+}
+
+impl Default for CaptureHook {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            pattern: Default::default(),
+            format: Default::default(),
+            kind: Default::default(),
+            replace_entry: false,
+            timeout_ms: 5000,
+        }
+    }
+}
+
+/// New entry handed to capture hooks
+#[derive(Debug, Clone)]
+pub struct CapturedEntry {
+    pub entry_id: i32,
+    pub text: String,
+    pub formats: Vec<ClipboardFormat>,
+    pub kinds: Vec<ContentKind>,
+    pub tags: Vec<String>,
+    pub source_app: String,
+}
+
+impl CaptureHook {
+    pub fn matches(&self, entry: &CapturedEntry) -> bool {
+        !self.command.trim().is_empty()
+            && self.format.matches(&entry.formats)
+            && self.kind.matches(&entry.kinds)
+            && self.pattern.is_match(&entry.text)
+    }
+
+    /// Runs the command and returns its output, it is killed once the timeout elapses
+    pub async fn run(&self, entry: &CapturedEntry) -> anyhow::Result<String> {
+        let formats = entry
+            .formats
+            .iter()
+            .map(|format| format!("{format:?}").to_lowercase())
+            .collect::<Vec<_>>();
+        let mut child = shell_command(&self.command)
+            .env("JOY_CLIPPY_ENTRY_ID", entry.entry_id.to_string())
+            .env("JOY_CLIPPY_FORMATS", formats.join(","))
+            .env("JOY_CLIPPY_TAGS", entry.tags.join(","))
+            .env("JOY_CLIPPY_SOURCE_APP", &entry.source_app)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // Written concurrently so a command producing output before reading all its input
+        // does not deadlock
+        let mut stdin = child.stdin.take().unwrap();
+        let text = entry.text.clone();
+        tokio::spawn(async move {
+            if let Err(e) = stdin.write_all(text.as_bytes()).await {
+                tracing::error!("Could not write to capture hook stdin\n{e:?}");
+            }
+        });
+
+        let output = timeout(
+            Duration::from_millis(self.timeout_ms),
+            child.wait_with_output(),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Timed out after {} ms", self.timeout_ms))??;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Exited with {}\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let mut stdout = String::from_utf8(output.stdout)?;
+        // Most commands end their output with a line break
+        if stdout.ends_with('\n') && !entry.text.ends_with('\n') {
+            stdout.pop();
+            if stdout.ends_with('\r') {
+                stdout.pop();
+            }
+        }
+        Ok(stdout)
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command
+}
+
+#[cfg(windows)]
+fn shell_command(command_line: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(command_line);
+    command
+}
@@ -0,0 +1,98 @@
+use std::fmt::{self, Display};
+
+use iced::keyboard::Modifiers;
+use serde::{Deserialize, Serialize};
+
+use crate::app::Shortcut;
+
+/// Serializable counterpart of iced `Modifiers`, compared against the global modifiers state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ModifierSet {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub logo: bool,
+}
+
+impl ModifierSet {
+    pub fn matches(&self, modifiers: Modifiers) -> bool {
+        *self == ModifierSet::from(modifiers)
+    }
+}
+
+impl From<Modifiers> for ModifierSet {
+    fn from(modifiers: Modifiers) -> Self {
+        Self {
+            ctrl: modifiers.control(),
+            alt: modifiers.alt(),
+            shift: modifiers.shift(),
+            logo: modifiers.logo(),
+        }
+    }
+}
+
+impl Display for ModifierSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+            (self.logo, "Logo"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+        .collect::<Vec<_>>();
+        f.write_str(&names.join(" + "))
+    }
+}
+
+/// Global shortcut that can be stored in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalShortcut {
+    pub modifiers: ModifierSet,
+    pub key: rdev::Key,
+}
+
+impl GlobalShortcut {
+    pub fn matches(&self, modifiers: Modifiers, event: &rdev::Event) -> bool {
+        matches!(event.event_type, rdev::EventType::KeyPress(key) if key == self.key)
+            && self.modifiers.matches(modifiers)
+    }
+}
+
+impl From<&Shortcut> for GlobalShortcut {
+    fn from(shortcut: &Shortcut) -> Self {
+        Self {
+            modifiers: shortcut.modifiers.into(),
+            key: shortcut.rdev_key,
+        }
+    }
+}
+
+impl Display for GlobalShortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers == ModifierSet::default() {
+            write!(f, "{:?}", self.key)
+        } else {
+            write!(f, "{} + {:?}", self.modifiers, self.key)
+        }
+    }
+}
+
+/// Position of a digit key on the number row, `Num1` being 1.
+pub fn digit(key: rdev::Key) -> Option<usize> {
+    use rdev::Key;
+
+    Some(match key {
+        Key::Num1 => 1,
+        Key::Num2 => 2,
+        Key::Num3 => 3,
+        Key::Num4 => 4,
+        Key::Num5 => 5,
+        Key::Num6 => 6,
+        Key::Num7 => 7,
+        Key::Num8 => 8,
+        Key::Num9 => 9,
+        _ => return None,
+    })
+}
@@ -0,0 +1,215 @@
+use std::fmt::{self, Display};
+
+use rdev::Key;
+use serde::{Deserialize, Serialize};
+
+/// Layout the target expects, used to find which physical keys produce a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+}
+
+impl KeyboardLayout {
+    pub const ALL: [KeyboardLayout; 2] = [KeyboardLayout::Qwerty, KeyboardLayout::Azerty];
+
+    pub fn chord(self, c: char) -> Option<Vec<Key>> {
+        match c {
+            ' ' => return Some(vec![Key::Space]),
+            '\n' => return Some(vec![Key::Return]),
+            '\t' => return Some(vec![Key::Tab]),
+            _ => {}
+        }
+
+        let (key, level) = match self {
+            KeyboardLayout::Qwerty => qwerty(c)?,
+            KeyboardLayout::Azerty => azerty(c)?,
+        };
+        Some(match level {
+            Level::Base => vec![key],
+            Level::Shift => vec![Key::ShiftLeft, key],
+            Level::AltGr => vec![Key::AltGr, key],
+        })
+    }
+}
+
+impl Display for KeyboardLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeyboardLayout::Qwerty => "QWERTY (US)",
+            KeyboardLayout::Azerty => "AZERTY (FR)",
+        })
+    }
+}
+
+enum Level {
+    Base,
+    Shift,
+    AltGr,
+}
+
+fn letter_key(c: char) -> Option<Key> {
+    Some(match c.to_ascii_lowercase() {
+        'a' => Key::KeyA,
+        'b' => Key::KeyB,
+        'c' => Key::KeyC,
+        'd' => Key::KeyD,
+        'e' => Key::KeyE,
+        'f' => Key::KeyF,
+        'g' => Key::KeyG,
+        'h' => Key::KeyH,
+        'i' => Key::KeyI,
+        'j' => Key::KeyJ,
+        'k' => Key::KeyK,
+        'l' => Key::KeyL,
+        'm' => Key::KeyM,
+        'n' => Key::KeyN,
+        'o' => Key::KeyO,
+        'p' => Key::KeyP,
+        'q' => Key::KeyQ,
+        'r' => Key::KeyR,
+        's' => Key::KeyS,
+        't' => Key::KeyT,
+        'u' => Key::KeyU,
+        'v' => Key::KeyV,
+        'w' => Key::KeyW,
+        'x' => Key::KeyX,
+        'y' => Key::KeyY,
+        'z' => Key::KeyZ,
+        _ => return None,
+    })
+}
+
+const DIGIT_KEYS: [Key; 10] = [
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+fn qwerty(c: char) -> Option<(Key, Level)> {
+    if c.is_ascii_alphabetic() {
+        let level = if c.is_ascii_uppercase() {
+            Level::Shift
+        } else {
+            Level::Base
+        };
+        return Some((letter_key(c)?, level));
+    }
+    if let Some(digit) = c.to_digit(10) {
+        return Some((DIGIT_KEYS[digit as usize], Level::Base));
+    }
+
+    Some(match c {
+        '!' => (Key::Num1, Level::Shift),
+        '@' => (Key::Num2, Level::Shift),
+        '#' => (Key::Num3, Level::Shift),
+        '$' => (Key::Num4, Level::Shift),
+        '%' => (Key::Num5, Level::Shift),
+        '^' => (Key::Num6, Level::Shift),
+        '&' => (Key::Num7, Level::Shift),
+        '*' => (Key::Num8, Level::Shift),
+        '(' => (Key::Num9, Level::Shift),
+        ')' => (Key::Num0, Level::Shift),
+        '-' => (Key::Minus, Level::Base),
+        '_' => (Key::Minus, Level::Shift),
+        '=' => (Key::Equal, Level::Base),
+        '+' => (Key::Equal, Level::Shift),
+        '[' => (Key::LeftBracket, Level::Base),
+        '{' => (Key::LeftBracket, Level::Shift),
+        ']' => (Key::RightBracket, Level::Base),
+        '}' => (Key::RightBracket, Level::Shift),
+        '\\' => (Key::BackSlash, Level::Base),
+        '|' => (Key::BackSlash, Level::Shift),
+        ';' => (Key::SemiColon, Level::Base),
+        ':' => (Key::SemiColon, Level::Shift),
+        '\'' => (Key::Quote, Level::Base),
+        '"' => (Key::Quote, Level::Shift),
+        '`' => (Key::BackQuote, Level::Base),
+        '~' => (Key::BackQuote, Level::Shift),
+        ',' => (Key::Comma, Level::Base),
+        '<' => (Key::Comma, Level::Shift),
+        '.' => (Key::Dot, Level::Base),
+        '>' => (Key::Dot, Level::Shift),
+        '/' => (Key::Slash, Level::Base),
+        '?' => (Key::Slash, Level::Shift),
+        _ => return None,
+    })
+}
+
+fn azerty(c: char) -> Option<(Key, Level)> {
+    if c.is_ascii_alphabetic() {
+        let level = if c.is_ascii_uppercase() {
+            Level::Shift
+        } else {
+            Level::Base
+        };
+        // Keys are named after their QWERTY position
+        let key = match c.to_ascii_lowercase() {
+            'a' => Key::KeyQ,
+            'z' => Key::KeyW,
+            'q' => Key::KeyA,
+            'w' => Key::KeyZ,
+            'm' => Key::SemiColon,
+            _ => letter_key(c)?,
+        };
+        return Some((key, level));
+    }
+    if let Some(digit) = c.to_digit(10) {
+        return Some((DIGIT_KEYS[digit as usize], Level::Shift));
+    }
+
+    Some(match c {
+        '&' => (Key::Num1, Level::Base),
+        'é' => (Key::Num2, Level::Base),
+        '"' => (Key::Num3, Level::Base),
+        '\'' => (Key::Num4, Level::Base),
+        '(' => (Key::Num5, Level::Base),
+        '-' => (Key::Num6, Level::Base),
+        'è' => (Key::Num7, Level::Base),
+        '_' => (Key::Num8, Level::Base),
+        'ç' => (Key::Num9, Level::Base),
+        'à' => (Key::Num0, Level::Base),
+        ')' => (Key::Minus, Level::Base),
+        '°' => (Key::Minus, Level::Shift),
+        '=' => (Key::Equal, Level::Base),
+        '+' => (Key::Equal, Level::Shift),
+        '~' => (Key::Num2, Level::AltGr),
+        '#' => (Key::Num3, Level::AltGr),
+        '{' => (Key::Num4, Level::AltGr),
+        '[' => (Key::Num5, Level::AltGr),
+        '|' => (Key::Num6, Level::AltGr),
+        '`' => (Key::Num7, Level::AltGr),
+        '\\' => (Key::Num8, Level::AltGr),
+        '^' => (Key::Num9, Level::AltGr),
+        '@' => (Key::Num0, Level::AltGr),
+        ']' => (Key::Minus, Level::AltGr),
+        '}' => (Key::Equal, Level::AltGr),
+        '$' => (Key::RightBracket, Level::Base),
+        '£' => (Key::RightBracket, Level::Shift),
+        '¤' => (Key::RightBracket, Level::AltGr),
+        'ù' => (Key::Quote, Level::Base),
+        '%' => (Key::Quote, Level::Shift),
+        '*' => (Key::BackSlash, Level::Base),
+        'µ' => (Key::BackSlash, Level::Shift),
+        ',' => (Key::KeyM, Level::Base),
+        '?' => (Key::KeyM, Level::Shift),
+        ';' => (Key::Comma, Level::Base),
+        '.' => (Key::Comma, Level::Shift),
+        ':' => (Key::Dot, Level::Base),
+        '/' => (Key::Dot, Level::Shift),
+        '!' => (Key::Slash, Level::Base),
+        '§' => (Key::Slash, Level::Shift),
+        '<' => (Key::IntlBackslash, Level::Base),
+        '>' => (Key::IntlBackslash, Level::Shift),
+        '²' => (Key::BackQuote, Level::Base),
+        _ => return None,
+    })
+}
@@ -0,0 +1,304 @@
+use std::{
+    fmt::{self, Display},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+mod layout;
+mod rdev;
+#[cfg(test)]
+mod recording;
+#[cfg(target_os = "linux")]
+mod uinput;
+#[cfg(target_os = "linux")]
+mod xtest;
+
+pub use self::rdev::RdevInput;
+pub use layout::KeyboardLayout;
+#[cfg(test)]
+pub use recording::RecordingInput;
+#[cfg(target_os = "linux")]
+pub use uinput::UinputInput;
+#[cfg(target_os = "linux")]
+pub use xtest::XTestInput;
+
+const KEY_EVENT_DELAY: Duration = Duration::from_millis(20);
+
+pub trait InputBackend: Send + Sync {
+    fn press(&self, key: ::rdev::Key) -> anyhow::Result<()>;
+    fn release(&self, key: ::rdev::Key) -> anyhow::Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum InputBackendKind {
+    #[default]
+    Rdev,
+    XTest,
+    Uinput,
+}
+
+impl InputBackendKind {
+    pub const ALL: [InputBackendKind; 3] = [
+        InputBackendKind::Rdev,
+        InputBackendKind::XTest,
+        InputBackendKind::Uinput,
+    ];
+
+    pub fn create(self) -> anyhow::Result<Arc<dyn InputBackend>> {
+        match self {
+            InputBackendKind::Rdev => Ok(Arc::new(RdevInput)),
+            #[cfg(target_os = "linux")]
+            InputBackendKind::XTest => Ok(Arc::new(XTestInput::new()?)),
+            #[cfg(target_os = "linux")]
+            InputBackendKind::Uinput => Ok(Arc::new(UinputInput::new()?)),
+            #[cfg(not(target_os = "linux"))]
+            kind => Err(anyhow::anyhow!("{kind} input is only available on Linux")),
+        }
+    }
+}
+
+impl Display for InputBackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InputBackendKind::Rdev => "rdev",
+            InputBackendKind::XTest => "XTest",
+            InputBackendKind::Uinput => "uinput",
+        })
+    }
+}
+
+/// Presses every key of the chord in order then releases them in reverse order.
+pub async fn tap_chord(backend: &dyn InputBackend, chord: &[::rdev::Key]) -> anyhow::Result<()> {
+    for key in chord {
+        sleep(KEY_EVENT_DELAY).await;
+        backend.press(*key)?;
+        sleep(KEY_EVENT_DELAY * 2).await;
+    }
+    for key in chord.iter().rev() {
+        sleep(KEY_EVENT_DELAY).await;
+        backend.release(*key)?;
+        sleep(KEY_EVENT_DELAY * 2).await;
+    }
+    Ok(())
+}
+
+/// Extends the selection to the left by `count` characters.
+pub async fn select_back(backend: &dyn InputBackend, count: usize) -> anyhow::Result<()> {
+    backend.press(::rdev::Key::ShiftLeft)?;
+    for _ in 0..count {
+        backend.press(::rdev::Key::LeftArrow)?;
+        backend.release(::rdev::Key::LeftArrow)?;
+        sleep(KEY_EVENT_DELAY / 4).await;
+    }
+    backend.release(::rdev::Key::ShiftLeft)?;
+    sleep(KEY_EVENT_DELAY).await;
+    Ok(())
+}
+
+/// Types the text one character at a time, characters the layout cannot produce are skipped.
+pub async fn type_text(
+    backend: &dyn InputBackend,
+    text: &str,
+    layout: KeyboardLayout,
+    delay: Duration,
+) -> anyhow::Result<()> {
+    for c in text.chars().filter(|c| *c != '\r') {
+        let Some(chord) = layout.chord(c) else {
+            tracing::warn!("No key produces {c:?} with {layout} layout, skipping it");
+            continue;
+        };
+        for key in &chord {
+            backend.press(*key)?;
+        }
+        for key in chord.iter().rev() {
+            backend.release(*key)?;
+        }
+        sleep(delay).await;
+    }
+    Ok(())
+}
+
+/// Linux input event code of the key, as found in `input-event-codes.h`.
+#[cfg(target_os = "linux")]
+pub fn evdev_code(key: ::rdev::Key) -> Option<u16> {
+    use ::rdev::Key;
+
+    Some(match key {
+        Key::Escape => 1,
+        Key::Num1 => 2,
+        Key::Num2 => 3,
+        Key::Num3 => 4,
+        Key::Num4 => 5,
+        Key::Num5 => 6,
+        Key::Num6 => 7,
+        Key::Num7 => 8,
+        Key::Num8 => 9,
+        Key::Num9 => 10,
+        Key::Num0 => 11,
+        Key::Minus => 12,
+        Key::Equal => 13,
+        Key::Backspace => 14,
+        Key::Tab => 15,
+        Key::KeyQ => 16,
+        Key::KeyW => 17,
+        Key::KeyE => 18,
+        Key::KeyR => 19,
+        Key::KeyT => 20,
+        Key::KeyY => 21,
+        Key::KeyU => 22,
+        Key::KeyI => 23,
+        Key::KeyO => 24,
+        Key::KeyP => 25,
+        Key::LeftBracket => 26,
+        Key::RightBracket => 27,
+        Key::Return => 28,
+        Key::ControlLeft => 29,
+        Key::KeyA => 30,
+        Key::KeyS => 31,
+        Key::KeyD => 32,
+        Key::KeyF => 33,
+        Key::KeyG => 34,
+        Key::KeyH => 35,
+        Key::KeyJ => 36,
+        Key::KeyK => 37,
+        Key::KeyL => 38,
+        Key::SemiColon => 39,
+        Key::Quote => 40,
+        Key::BackQuote => 41,
+        Key::ShiftLeft => 42,
+        Key::BackSlash => 43,
+        Key::KeyZ => 44,
+        Key::KeyX => 45,
+        Key::KeyC => 46,
+        Key::KeyV => 47,
+        Key::KeyB => 48,
+        Key::KeyN => 49,
+        Key::KeyM => 50,
+        Key::Comma => 51,
+        Key::Dot => 52,
+        Key::Slash => 53,
+        Key::ShiftRight => 54,
+        Key::KpMultiply => 55,
+        Key::Alt => 56,
+        Key::Space => 57,
+        Key::CapsLock => 58,
+        Key::F1 => 59,
+        Key::F2 => 60,
+        Key::F3 => 61,
+        Key::F4 => 62,
+        Key::F5 => 63,
+        Key::F6 => 64,
+        Key::F7 => 65,
+        Key::F8 => 66,
+        Key::F9 => 67,
+        Key::F10 => 68,
+        Key::NumLock => 69,
+        Key::ScrollLock => 70,
+        Key::Kp7 => 71,
+        Key::Kp8 => 72,
+        Key::Kp9 => 73,
+        Key::KpMinus => 74,
+        Key::Kp4 => 75,
+        Key::Kp5 => 76,
+        Key::Kp6 => 77,
+        Key::KpPlus => 78,
+        Key::Kp1 => 79,
+        Key::Kp2 => 80,
+        Key::Kp3 => 81,
+        Key::Kp0 => 82,
+        Key::KpDelete => 83,
+        Key::IntlBackslash => 86,
+        Key::F11 => 87,
+        Key::F12 => 88,
+        Key::KpReturn => 96,
+        Key::ControlRight => 97,
+        Key::KpDivide => 98,
+        Key::PrintScreen => 99,
+        Key::AltGr => 100,
+        Key::Home => 102,
+        Key::UpArrow => 103,
+        Key::PageUp => 104,
+        Key::LeftArrow => 105,
+        Key::RightArrow => 106,
+        Key::End => 107,
+        Key::DownArrow => 108,
+        Key::PageDown => 109,
+        Key::Insert => 110,
+        Key::Delete => 111,
+        Key::Pause => 119,
+        Key::MetaLeft => 125,
+        Key::MetaRight => 126,
+        Key::Function | Key::Unknown(_) => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ::rdev::{EventType, Key};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn tap_chord_releases_in_reverse_order() {
+        let input = RecordingInput::new();
+
+        tap_chord(&input, &[Key::ControlLeft, Key::ShiftLeft, Key::KeyV])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            input.events(),
+            vec![
+                EventType::KeyPress(Key::ControlLeft),
+                EventType::KeyPress(Key::ShiftLeft),
+                EventType::KeyPress(Key::KeyV),
+                EventType::KeyRelease(Key::KeyV),
+                EventType::KeyRelease(Key::ShiftLeft),
+                EventType::KeyRelease(Key::ControlLeft),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn select_back_holds_shift_around_arrows() {
+        let input = RecordingInput::new();
+
+        select_back(&input, 2).await.unwrap();
+
+        assert_eq!(
+            input.events(),
+            vec![
+                EventType::KeyPress(Key::ShiftLeft),
+                EventType::KeyPress(Key::LeftArrow),
+                EventType::KeyRelease(Key::LeftArrow),
+                EventType::KeyPress(Key::LeftArrow),
+                EventType::KeyRelease(Key::LeftArrow),
+                EventType::KeyRelease(Key::ShiftLeft),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn type_text_uses_layout_chords() {
+        let input = RecordingInput::new();
+
+        type_text(&input, "Hi", KeyboardLayout::Qwerty, Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            input.events(),
+            vec![
+                EventType::KeyPress(Key::ShiftLeft),
+                EventType::KeyPress(Key::KeyH),
+                EventType::KeyRelease(Key::KeyH),
+                EventType::KeyRelease(Key::ShiftLeft),
+                EventType::KeyPress(Key::KeyI),
+                EventType::KeyRelease(Key::KeyI),
+            ]
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use rdev::{simulate, EventType, Key};
+
+use super::InputBackend;
+
+pub struct RdevInput;
+
+impl InputBackend for RdevInput {
+    fn press(&self, key: Key) -> anyhow::Result<()> {
+        simulate(&EventType::KeyPress(key))
+            .map_err(|_| anyhow::anyhow!("Could not simulate {key:?} press"))
+    }
+
+    fn release(&self, key: Key) -> anyhow::Result<()> {
+        simulate(&EventType::KeyRelease(key))
+            .map_err(|_| anyhow::anyhow!("Could not simulate {key:?} release"))
+    }
+}
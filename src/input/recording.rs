@@ -0,0 +1,31 @@
+use std::sync::{Arc, Mutex};
+
+use rdev::{EventType, Key};
+
+use super::InputBackend;
+
+/// Records simulated key events instead of sending them to the system.
+#[derive(Clone, Default)]
+pub struct RecordingInput(Arc<Mutex<Vec<EventType>>>);
+
+impl RecordingInput {
+    pub fn new() -> RecordingInput {
+        Default::default()
+    }
+
+    pub fn events(&self) -> Vec<EventType> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl InputBackend for RecordingInput {
+    fn press(&self, key: Key) -> anyhow::Result<()> {
+        self.0.lock().unwrap().push(EventType::KeyPress(key));
+        Ok(())
+    }
+
+    fn release(&self, key: Key) -> anyhow::Result<()> {
+        self.0.lock().unwrap().push(EventType::KeyRelease(key));
+        Ok(())
+    }
+}
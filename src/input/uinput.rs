@@ -0,0 +1,45 @@
+use std::{sync::Mutex, thread, time::Duration};
+
+use evdev::{uinput::VirtualDeviceBuilder, AttributeSet, EventType, InputEvent};
+use rdev::Key;
+
+use super::{evdev_code, InputBackend};
+
+/// Time given to the compositor to pick up the freshly created device.
+const DEVICE_SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+pub struct UinputInput(Mutex<evdev::uinput::VirtualDevice>);
+
+impl UinputInput {
+    pub fn new() -> anyhow::Result<UinputInput> {
+        let mut keys = AttributeSet::<evdev::Key>::new();
+        for code in 1..=evdev_code(Key::MetaRight).unwrap() {
+            keys.insert(evdev::Key::new(code));
+        }
+        let device = VirtualDeviceBuilder::new()?
+            .name("Joy clippy virtual keyboard")
+            .with_keys(&keys)?
+            .build()?;
+        thread::sleep(DEVICE_SETTLE_DELAY);
+        Ok(UinputInput(Mutex::new(device)))
+    }
+
+    fn emit(&self, key: Key, value: i32) -> anyhow::Result<()> {
+        let code = evdev_code(key).ok_or(anyhow::anyhow!("No keycode for {key:?}"))?;
+        self.0
+            .lock()
+            .unwrap()
+            .emit(&[InputEvent::new(EventType::KEY, code, value)])?;
+        Ok(())
+    }
+}
+
+impl InputBackend for UinputInput {
+    fn press(&self, key: Key) -> anyhow::Result<()> {
+        self.emit(key, 1)
+    }
+
+    fn release(&self, key: Key) -> anyhow::Result<()> {
+        self.emit(key, 0)
+    }
+}
@@ -0,0 +1,58 @@
+use rdev::Key;
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::{
+        xproto::{Window, KEY_PRESS_EVENT, KEY_RELEASE_EVENT},
+        xtest::{self, ConnectionExt},
+    },
+    rust_connection::RustConnection,
+    CURRENT_TIME,
+};
+
+use super::{evdev_code, InputBackend};
+
+/// X keycodes are evdev codes shifted by 8 with the evdev X driver, also used by XWayland.
+const X_KEYCODE_OFFSET: u16 = 8;
+
+pub struct XTestInput {
+    connection: RustConnection,
+    root: Window,
+}
+
+impl XTestInput {
+    pub fn new() -> anyhow::Result<XTestInput> {
+        let (connection, screen) = x11rb::connect(None)?;
+        let root = connection.setup().roots[screen].root;
+        connection
+            .extension_information(xtest::X11_EXTENSION_NAME)?
+            .ok_or(anyhow::anyhow!("XTest extension is not available"))?;
+        Ok(XTestInput { connection, root })
+    }
+
+    fn fake_key(&self, event_type: u8, key: Key) -> anyhow::Result<()> {
+        let keycode = evdev_code(key)
+            .ok_or(anyhow::anyhow!("No keycode for {key:?}"))?
+            + X_KEYCODE_OFFSET;
+        self.connection.xtest_fake_input(
+            event_type,
+            keycode as u8,
+            CURRENT_TIME,
+            self.root,
+            0,
+            0,
+            0,
+        )?;
+        self.connection.flush()?;
+        Ok(())
+    }
+}
+
+impl InputBackend for XTestInput {
+    fn press(&self, key: Key) -> anyhow::Result<()> {
+        self.fake_key(KEY_PRESS_EVENT, key)
+    }
+
+    fn release(&self, key: Key) -> anyhow::Result<()> {
+        self.fake_key(KEY_RELEASE_EVENT, key)
+    }
+}
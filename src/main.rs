@@ -2,8 +2,21 @@ use app::App;
 use tray::create_tray;
 
 mod app;
+mod classify;
 mod clipboard;
+mod color;
+mod config;
 mod db;
+mod external_editor;
+mod highlight;
+mod hooks;
+mod hotkey;
+mod input;
+mod paste;
+mod plugin;
+mod scripting;
+mod smart_action;
+mod transform;
 mod tray;
 mod utils;
 mod window;
@@ -25,10 +38,10 @@ fn main() {
         .with_test_writer()
         .init();
 
-    let _tray = create_tray();
+    let tray = create_tray();
 
     iced::daemon("Joy clippy", App::update, App::view)
         .subscription(App::subscription)
-        .run_with(App::new)
+        .run_with(move || App::new(tray))
         .unwrap();
 }
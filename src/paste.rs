@@ -0,0 +1,248 @@
+use std::fmt::{self, Display};
+
+use rdev::Key;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+#[cfg(not(target_os = "macos"))]
+pub const DEFAULT_PASTE_CHORD: [Key; 2] = [Key::ControlLeft, Key::KeyV];
+#[cfg(target_os = "macos")]
+pub const DEFAULT_PASTE_CHORD: [Key; 2] = [Key::MetaLeft, Key::KeyV];
+
+#[cfg(not(target_os = "macos"))]
+pub const UNDO_CHORD: [Key; 2] = [Key::ControlLeft, Key::KeyZ];
+#[cfg(target_os = "macos")]
+pub const UNDO_CHORD: [Key; 2] = [Key::MetaLeft, Key::KeyZ];
+
+/// How yank pop removes the previously pasted entry before pasting the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum YankPopReplace {
+    #[default]
+    Undo,
+    /// Selects the pasted characters with Shift+Left, the next paste overwrites them
+    SelectBack,
+}
+
+impl YankPopReplace {
+    pub const ALL: [YankPopReplace; 2] = [YankPopReplace::Undo, YankPopReplace::SelectBack];
+}
+
+impl Display for YankPopReplace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            YankPopReplace::Undo => "Undo",
+            YankPopReplace::SelectBack => "Select back",
+        })
+    }
+}
+
+/// Key sent after each paste from the queue, to move to the next form field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PasteSuffix {
+    #[default]
+    Nothing,
+    Tab,
+    Enter,
+}
+
+impl PasteSuffix {
+    pub const ALL: [PasteSuffix; 3] = [PasteSuffix::Nothing, PasteSuffix::Tab, PasteSuffix::Enter];
+
+    pub fn key(self) -> Option<Key> {
+        match self {
+            PasteSuffix::Nothing => None,
+            PasteSuffix::Tab => Some(Key::Tab),
+            PasteSuffix::Enter => Some(Key::Return),
+        }
+    }
+}
+
+impl Display for PasteSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PasteSuffix::Nothing => "Nothing",
+            PasteSuffix::Tab => "Tab",
+            PasteSuffix::Enter => "Enter",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PasteAction {
+    Chord(Vec<Key>),
+    CopyOnly,
+}
+
+impl PasteAction {
+    pub fn presets() -> Vec<PasteAction> {
+        vec![
+            PasteAction::Chord(vec![Key::ControlLeft, Key::KeyV]),
+            PasteAction::Chord(vec![Key::ControlLeft, Key::ShiftLeft, Key::KeyV]),
+            PasteAction::Chord(vec![Key::ShiftLeft, Key::Insert]),
+            PasteAction::Chord(vec![Key::MetaLeft, Key::KeyV]),
+            PasteAction::CopyOnly,
+        ]
+    }
+}
+
+impl Display for PasteAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasteAction::Chord(chord) => f.write_str(&chord_string(chord)),
+            PasteAction::CopyOnly => f.write_str("Copy only"),
+        }
+    }
+}
+
+/// Overrides the paste action when the focused window matches.
+/// Empty patterns match anything, others are case insensitive substrings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasteRule {
+    pub window_class: String,
+    pub window_title: String,
+    pub action: PasteAction,
+}
+
+impl Default for PasteRule {
+    fn default() -> Self {
+        Self {
+            window_class: String::new(),
+            window_title: String::new(),
+            action: PasteAction::CopyOnly,
+        }
+    }
+}
+
+impl PasteRule {
+    fn matches(&self, window: &ActiveWindow) -> bool {
+        fn contains(haystack: &str, needle: &str) -> bool {
+            needle.is_empty() || haystack.to_lowercase().contains(&needle.to_lowercase())
+        }
+
+        (!self.window_class.is_empty() || !self.window_title.is_empty())
+            && contains(&window.class, &self.window_class)
+            && contains(&window.title, &self.window_title)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ActiveWindow {
+    pub class: String,
+    pub title: String,
+}
+
+pub fn resolve_action(config: &Config, window: Option<&ActiveWindow>) -> PasteAction {
+    window
+        .and_then(|window| {
+            config
+                .paste_rules
+                .iter()
+                .find(|rule| rule.matches(window))
+        })
+        .map(|rule| rule.action.clone())
+        .unwrap_or_else(|| PasteAction::Chord(config.paste_chord.clone()))
+}
+
+pub fn chord_string(chord: &[Key]) -> String {
+    chord
+        .iter()
+        .map(|key| format!("{key:?}"))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// X11 connection shared by the active window lookups, with the atoms they use
+#[cfg(target_os = "linux")]
+struct X11 {
+    connection: x11rb::rust_connection::RustConnection,
+    root: u32,
+    net_active_window: u32,
+    net_wm_name: u32,
+}
+
+/// Connected on first use, `None` when there is no X server to talk to, e.g. on Wayland
+#[cfg(target_os = "linux")]
+static X11_CONNECTION: std::sync::LazyLock<Option<X11>> = std::sync::LazyLock::new(|| {
+    use x11rb::{connection::Connection, protocol::xproto::ConnectionExt};
+
+    let connect = || -> anyhow::Result<X11> {
+        let (connection, screen) = x11rb::connect(None)?;
+        let root = connection.setup().roots[screen].root;
+        let atom = |name: &[u8]| -> anyhow::Result<u32> {
+            Ok(connection.intern_atom(false, name)?.reply()?.atom)
+        };
+        let net_active_window = atom(b"_NET_ACTIVE_WINDOW")?;
+        let net_wm_name = atom(b"_NET_WM_NAME")?;
+        Ok(X11 {
+            connection,
+            root,
+            net_active_window,
+            net_wm_name,
+        })
+    };
+    connect()
+        .inspect_err(|e| {
+            tracing::debug!("X11 is unavailable, the active window will not be known\n{e:?}")
+        })
+        .ok()
+});
+
+#[cfg(target_os = "linux")]
+pub fn active_window() -> Option<ActiveWindow> {
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+    fn query(x11: &X11) -> anyhow::Result<Option<ActiveWindow>> {
+        let connection = &x11.connection;
+        let property = |window: u32, property: u32| -> anyhow::Result<Vec<u8>> {
+            Ok(connection
+                .get_property(false, window, property, AtomEnum::ANY, 0, u32::MAX / 4)?
+                .reply()?
+                .value)
+        };
+
+        let active_window = connection
+            .get_property(
+                false,
+                x11.root,
+                x11.net_active_window,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )?
+            .reply()?
+            .value32()
+            .and_then(|mut values| values.next())
+            .filter(|window| *window != x11rb::NONE);
+        let Some(window) = active_window else {
+            return Ok(None);
+        };
+
+        // WM_CLASS holds "instance\0class\0"
+        let class = property(window, AtomEnum::WM_CLASS.into())?
+            .split(|byte| *byte == 0)
+            .filter(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut title = property(window, x11.net_wm_name)?;
+        if title.is_empty() {
+            title = property(window, AtomEnum::WM_NAME.into())?;
+        }
+
+        Ok(Some(ActiveWindow {
+            class,
+            title: String::from_utf8_lossy(&title).into_owned(),
+        }))
+    }
+
+    query(X11_CONNECTION.as_ref()?).unwrap_or_else(|e| {
+        tracing::error!("Could not retrieve active window\n{e:?}");
+        None
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn active_window() -> Option<ActiveWindow> {
+    None
+}
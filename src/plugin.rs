@@ -0,0 +1,491 @@
+//! WebAssembly plugins loaded from the `plugins` folder of the config dir.
+//!
+//! Interface version 1. Strings are UTF-8, passed to the plugin as a pointer and a length
+//! into its memory and returned packed in an `i64` as `pointer << 32 | length`.
+//! Once a call returned, the host frees both its input and its output, so a returned
+//! string must be allocated with `joy_alloc` and not be kept by the plugin.
+//! A plugin exports:
+//! - `memory` and `joy_alloc(len: i32) -> i32`, used by the host to pass input strings
+//! - `joy_dealloc(ptr: i32, len: i32)`, used by the host to free input and output strings
+//! - `joy_abi_version() -> i32`, returning [`ABI_VERSION`]
+//! - `joy_manifest() -> i64`, a TOML [`Manifest`]
+//! - `joy_transform(index: i32, ptr: i32, len: i32) -> i64` when it lists transformations
+//! - `joy_classify(ptr: i32, len: i32) -> i64` when it is a classifier, empty for no class
+//! - `joy_preview(ptr: i32, len: i32) -> i64` when it renders previews, empty for none
+//!
+//! It may import the following from the `joy_clippy` module, calling one whose
+//! [`Capability`] was not granted in the settings traps:
+//! - `log(ptr: i32, len: i32)` requires `log`
+//! - `now_millis() -> i64` requires `clock`
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use wasmtime::{
+    Caller, Engine, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+use crate::utils::project_dirs;
+
+pub const ABI_VERSION: i32 = 1;
+const PLUGINS_DIR: &str = "plugins";
+const PLUGIN_EXTENSION: &str = "wasm";
+const HOST_MODULE: &str = "joy_clippy";
+/// Bounds the work done by a single call into a plugin
+const FUEL_PER_CALL: u64 = 50_000_000;
+const MAX_MEMORY_BYTES: usize = 64 << 20;
+
+/// Host function a plugin can be allowed to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    Log,
+    Clock,
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Capability::Log => "Write to the log",
+            Capability::Clock => "Read the current time",
+        })
+    }
+}
+
+/// Describes what a plugin provides and the capabilities it asks for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    /// Names of the transformations, their index is passed to `joy_transform`
+    pub transforms: Vec<String>,
+    pub classifier: bool,
+    pub preview: bool,
+    pub capabilities: Vec<Capability>,
+}
+
+/// User choices for a plugin, stored in the config by plugin file name.
+/// Plugins start disabled, they only run once turned on in the settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginSettings {
+    pub enabled: bool,
+    pub granted: Vec<Capability>,
+}
+
+/// Load state of a plugin, shown in the plugin manager
+#[derive(Debug, Clone)]
+pub struct PluginStatus {
+    pub file: String,
+    /// Only known once the plugin is enabled and loaded
+    pub manifest: Option<Manifest>,
+    /// Last load or runtime error
+    pub error: Option<String>,
+}
+
+struct HostState {
+    file: String,
+    granted: Vec<Capability>,
+    limits: StoreLimits,
+}
+
+struct PluginInstance {
+    store: Store<HostState>,
+    instance: Instance,
+    memory: Memory,
+    error: Option<String>,
+}
+
+impl fmt::Debug for PluginInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginInstance")
+            .field("file", &self.store.data().file)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PluginInstance {
+    fn new(
+        engine: &Engine,
+        path: &Path,
+        file: String,
+        granted: Vec<Capability>,
+    ) -> anyhow::Result<PluginInstance> {
+        let module = Module::from_file(engine, path)?;
+        let mut store = Store::new(
+            engine,
+            HostState {
+                file,
+                granted,
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_MEMORY_BYTES)
+                    .instances(1)
+                    .build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = host_linker(engine)?.instantiate(&mut store, &module)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or(anyhow::anyhow!("Plugin does not export its memory"))?;
+        let abi_version = instance
+            .get_typed_func::<(), i32>(&mut store, "joy_abi_version")?
+            .call(&mut store, ())?;
+        anyhow::ensure!(
+            abi_version == ABI_VERSION,
+            "Plugin targets interface version {abi_version}, expected {ABI_VERSION}"
+        );
+        instance.get_typed_func::<(i32, i32), ()>(&mut store, "joy_dealloc")?;
+        Ok(PluginInstance {
+            store,
+            instance,
+            memory,
+            error: None,
+        })
+    }
+
+    fn manifest(&mut self) -> anyhow::Result<Manifest> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+        let packed = self
+            .instance
+            .get_typed_func::<(), i64>(&mut self.store, "joy_manifest")?
+            .call(&mut self.store, ())?;
+        let manifest = self.read_output(packed);
+        self.free_output(packed)?;
+        Ok(toml::from_str(&manifest?)?)
+    }
+
+    /// Calls a string to string export, `index` is passed first when given
+    fn call(&mut self, function: &str, index: Option<i32>, input: &str) -> anyhow::Result<String> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+        let (ptr, len) = self.write_input(input)?;
+        let packed = match index {
+            Some(index) => self
+                .instance
+                .get_typed_func::<(i32, i32, i32), i64>(&mut self.store, function)?
+                .call(&mut self.store, (index, ptr, len))?,
+            None => self
+                .instance
+                .get_typed_func::<(i32, i32), i64>(&mut self.store, function)?
+                .call(&mut self.store, (ptr, len))?,
+        };
+        let output = self.read_output(packed);
+        self.dealloc(ptr, len)?;
+        self.free_output(packed)?;
+        output
+    }
+
+    fn write_input(&mut self, input: &str) -> anyhow::Result<(i32, i32)> {
+        let len = i32::try_from(input.len())?;
+        let ptr = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "joy_alloc")?
+            .call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input.as_bytes())?;
+        Ok((ptr, len))
+    }
+
+    fn dealloc(&mut self, ptr: i32, len: i32) -> anyhow::Result<()> {
+        self.instance
+            .get_typed_func::<(i32, i32), ()>(&mut self.store, "joy_dealloc")?
+            .call(&mut self.store, (ptr, len))?;
+        Ok(())
+    }
+
+    /// Empty strings need no allocation, plugins may return them as a null pointer
+    fn free_output(&mut self, packed: i64) -> anyhow::Result<()> {
+        let (ptr, len) = unpack(packed);
+        if len == 0 {
+            return Ok(());
+        }
+        self.dealloc(ptr as i32, len as i32)
+    }
+
+    fn read_output(&self, packed: i64) -> anyhow::Result<String> {
+        let (ptr, len) = unpack(packed);
+        let bytes = self
+            .memory
+            .data(&self.store)
+            .get(ptr..ptr + len)
+            .ok_or(anyhow::anyhow!(
+                "Plugin returned a string out of its memory"
+            ))?;
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+fn unpack(packed: i64) -> (usize, usize) {
+    ((packed >> 32) as u32 as usize, packed as u32 as usize)
+}
+
+/// Runs the call and keeps its error for the plugin manager
+fn call(
+    instance: &Mutex<PluginInstance>,
+    function: &str,
+    index: Option<i32>,
+    input: &str,
+) -> anyhow::Result<String> {
+    let mut instance = instance
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Plugin crashed during a previous call"))?;
+    let result = instance.call(function, index, input);
+    // Only the error of the last call is shown
+    instance.error = result.as_ref().err().map(ToString::to_string);
+    result
+}
+
+fn host_linker(engine: &Engine) -> anyhow::Result<Linker<HostState>> {
+    fn require(caller: &Caller<'_, HostState>, capability: Capability) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            caller.data().granted.contains(&capability),
+            "Capability \"{capability}\" was not granted"
+        );
+        Ok(())
+    }
+
+    let mut linker = Linker::new(engine);
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |caller: Caller<'_, HostState>, ptr: i32, len: i32| -> anyhow::Result<()> {
+            require(&caller, Capability::Log)?;
+            let memory = caller
+                .get_export("memory")
+                .and_then(Extern::into_memory)
+                .ok_or(anyhow::anyhow!("Plugin does not export its memory"))?;
+            let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+            let message = memory
+                .data(&caller)
+                .get(ptr..ptr + len)
+                .ok_or(anyhow::anyhow!("Log message out of the plugin memory"))?;
+            tracing::info!(
+                "Plugin {}: {}",
+                caller.data().file,
+                String::from_utf8_lossy(message)
+            );
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "now_millis",
+        |caller: Caller<'_, HostState>| -> anyhow::Result<i64> {
+            require(&caller, Capability::Clock)?;
+            Ok(chrono::Local::now().timestamp_millis())
+        },
+    )?;
+    Ok(linker)
+}
+
+#[derive(Debug, Clone)]
+struct Plugin {
+    file: String,
+    manifest: Option<Manifest>,
+    instance: Option<Arc<Mutex<PluginInstance>>>,
+    /// Set when the plugin could not be loaded
+    error: Option<String>,
+}
+
+impl Plugin {
+    /// Instance and manifest of plugins that loaded successfully
+    fn loaded(&self) -> Option<(&Arc<Mutex<PluginInstance>>, &Manifest)> {
+        self.instance.as_ref().zip(self.manifest.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PluginHost {
+    plugins: Vec<Plugin>,
+}
+
+impl PluginHost {
+    /// Instantiates every enabled plugin of the plugins folder with its granted capabilities
+    pub fn load(settings: &BTreeMap<String, PluginSettings>) -> PluginHost {
+        let paths = match plugin_paths() {
+            Ok(paths) => paths,
+            Err(e) => {
+                tracing::error!("Could not list plugins\n{e:?}");
+                return PluginHost::default();
+            }
+        };
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = match Engine::new(&config) {
+            Ok(engine) => engine,
+            Err(e) => {
+                tracing::error!("Could not create the plugin engine\n{e:?}");
+                return PluginHost::default();
+            }
+        };
+
+        let plugins = paths
+            .into_iter()
+            .map(|path| {
+                let file = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let settings = settings.get(&file).cloned().unwrap_or_default();
+                if !settings.enabled {
+                    return Plugin {
+                        file,
+                        manifest: None,
+                        instance: None,
+                        error: None,
+                    };
+                }
+                let loaded = PluginInstance::new(&engine, &path, file.clone(), settings.granted)
+                    .and_then(|mut instance| Ok((instance.manifest()?, instance)));
+                match loaded {
+                    Ok((manifest, instance)) => Plugin {
+                        file,
+                        manifest: Some(manifest),
+                        instance: Some(Arc::new(Mutex::new(instance))),
+                        error: None,
+                    },
+                    Err(e) => {
+                        tracing::error!("Could not load plugin {file}\n{e:?}");
+                        Plugin {
+                            file,
+                            manifest: None,
+                            instance: None,
+                            error: Some(e.to_string()),
+                        }
+                    }
+                }
+            })
+            .collect();
+        PluginHost { plugins }
+    }
+
+    pub fn statuses(&self) -> Vec<PluginStatus> {
+        self.plugins
+            .iter()
+            .map(|plugin| PluginStatus {
+                file: plugin.file.clone(),
+                manifest: plugin.manifest.clone(),
+                error: plugin.error.clone().or_else(|| {
+                    plugin
+                        .instance
+                        .as_ref()
+                        .and_then(|instance| instance.lock().ok()?.error.clone())
+                }),
+            })
+            .collect()
+    }
+
+    pub fn transforms(&self) -> Vec<PluginTransform> {
+        self.plugins
+            .iter()
+            .filter_map(Plugin::loaded)
+            .flat_map(|(instance, manifest)| {
+                manifest
+                    .transforms
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| PluginTransform {
+                        name: name.clone(),
+                        plugin: manifest.name.clone(),
+                        index: index as i32,
+                        instance: instance.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    pub fn classifiers(&self) -> Vec<PluginClassifier> {
+        self.plugins
+            .iter()
+            .filter_map(Plugin::loaded)
+            .filter(|(_, manifest)| manifest.classifier)
+            .map(|(instance, manifest)| PluginClassifier {
+                plugin: manifest.name.clone(),
+                instance: instance.clone(),
+            })
+            .collect()
+    }
+
+    pub fn previewers(&self) -> Vec<PluginPreview> {
+        self.plugins
+            .iter()
+            .filter_map(Plugin::loaded)
+            .filter(|(_, manifest)| manifest.preview)
+            .map(|(instance, manifest)| PluginPreview {
+                plugin: manifest.name.clone(),
+                instance: instance.clone(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginTransform {
+    pub name: String,
+    pub plugin: String,
+    index: i32,
+    instance: Arc<Mutex<PluginInstance>>,
+}
+
+impl PluginTransform {
+    pub fn apply(&self, text: &str) -> anyhow::Result<String> {
+        call(&self.instance, "joy_transform", Some(self.index), text)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginClassifier {
+    pub plugin: String,
+    instance: Arc<Mutex<PluginInstance>>,
+}
+
+impl PluginClassifier {
+    /// Class given to the text, empty when it has none
+    pub fn classify(&self, text: &str) -> anyhow::Result<String> {
+        call(&self.instance, "joy_classify", None, text)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginPreview {
+    pub plugin: String,
+    instance: Arc<Mutex<PluginInstance>>,
+}
+
+impl PluginPreview {
+    /// Preview of the text, empty when the plugin has nothing to show for it
+    pub fn render(&self, text: &str) -> anyhow::Result<String> {
+        call(&self.instance, "joy_preview", None, text)
+    }
+}
+
+pub fn plugins_dir() -> anyhow::Result<PathBuf> {
+    let mut plugins_dir = project_dirs()?.config_dir().to_path_buf();
+    plugins_dir.push(PLUGINS_DIR);
+    Ok(plugins_dir)
+}
+
+fn plugin_paths() -> anyhow::Result<Vec<PathBuf>> {
+    let plugins_dir = plugins_dir()?;
+    if !plugins_dir.try_exists()? {
+        fs::create_dir_all(&plugins_dir)?;
+    }
+    let mut paths = fs::read_dir(plugins_dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == PLUGIN_EXTENSION)
+    });
+    paths.sort();
+    Ok(paths)
+}
@@ -0,0 +1,329 @@
+//! Rhai scripts loaded from the `scripts` folder of the config dir.
+//!
+//! A script can define the following functions, `this` being the entry as a map
+//! with `text`, `formats`, `tags` and `source_app`:
+//! - `on_capture()` runs on every copy, returning `false` vetoes the capture and
+//!   changes made to `this.text` and `this.tags` are stored
+//! - `transform()` returns the new text, or changes `this.text`, the script is then
+//!   listed in the transformation palette of the history window
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use rhai::{
+    module_resolvers::DummyModuleResolver, CallFnOptions, Dynamic, Engine, Map, Scope, AST,
+};
+
+use crate::utils::project_dirs;
+
+const SCRIPTS_DIR: &str = "scripts";
+const SCRIPT_EXTENSION: &str = "rhai";
+/// Stops runaway scripts, roughly a few milliseconds of work
+const MAX_OPERATIONS: u64 = 1_000_000;
+const CAPTURE_HOOK: &str = "on_capture";
+const TRANSFORM_HOOK: &str = "transform";
+
+/// Entry as seen by scripts
+#[derive(Debug, Clone, Default)]
+pub struct ScriptEntry {
+    pub text: String,
+    pub formats: Vec<String>,
+    pub tags: Vec<String>,
+    /// Class of the window focused when the entry was copied, empty when unknown
+    pub source_app: String,
+}
+
+impl ScriptEntry {
+    fn to_dynamic(&self) -> Dynamic {
+        fn strings(strings: &[String]) -> Dynamic {
+            Dynamic::from_array(strings.iter().cloned().map(Dynamic::from).collect())
+        }
+
+        let mut map = Map::new();
+        map.insert("text".into(), self.text.clone().into());
+        map.insert("formats".into(), strings(&self.formats));
+        map.insert("tags".into(), strings(&self.tags));
+        map.insert("source_app".into(), self.source_app.clone().into());
+        Dynamic::from_map(map)
+    }
+
+    fn update_from(&mut self, this: Dynamic) -> anyhow::Result<()> {
+        let mut map = this
+            .try_cast::<Map>()
+            .ok_or(anyhow::anyhow!("`this` is no longer a map"))?;
+        if let Some(text) = map.remove("text") {
+            self.text = text
+                .into_string()
+                .map_err(|kind| anyhow::anyhow!("`text` must be a string, got {kind}"))?;
+        }
+        if let Some(tags) = map.remove("tags") {
+            self.tags = tags
+                .into_array()
+                .map_err(|kind| anyhow::anyhow!("`tags` must be an array, got {kind}"))?
+                .into_iter()
+                .map(|tag| tag.to_string())
+                .collect();
+        }
+        Ok(())
+    }
+}
+
+/// Load state of a script, shown in the settings window
+#[derive(Debug, Clone)]
+pub struct ScriptStatus {
+    pub name: String,
+    /// Last compilation or runtime error
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Script {
+    name: String,
+    ast: Option<Arc<AST>>,
+    /// Shared with the capture hooks, they run off the UI thread
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Script {
+    fn defines(&self, hook: &str) -> bool {
+        self.ast
+            .as_ref()
+            .is_some_and(|ast| ast.iter_functions().any(|function| function.name == hook))
+    }
+}
+
+#[derive(Debug)]
+pub struct Scripts {
+    engine: Arc<Engine>,
+    scripts: Vec<Script>,
+}
+
+impl Default for Scripts {
+    fn default() -> Self {
+        Self {
+            engine: Arc::new(sandboxed_engine()),
+            scripts: Vec::new(),
+        }
+    }
+}
+
+impl Scripts {
+    /// Compiles every script of the scripts folder, failing ones are kept with their error
+    pub fn load() -> Scripts {
+        let mut scripts = Scripts::default();
+        let paths = match script_paths() {
+            Ok(paths) => paths,
+            Err(e) => {
+                tracing::error!("Could not list scripts\n{e:?}");
+                return scripts;
+            }
+        };
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let compiled = fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|source| Ok(scripts.engine.compile(source)?));
+            let script = match compiled {
+                Ok(ast) => Script {
+                    name,
+                    ast: Some(Arc::new(ast)),
+                    error: Default::default(),
+                },
+                Err(e) => {
+                    tracing::error!("Could not compile script {name}\n{e:?}");
+                    Script {
+                        name,
+                        ast: None,
+                        error: Arc::new(Mutex::new(Some(e.to_string()))),
+                    }
+                }
+            };
+            scripts.scripts.push(script);
+        }
+        scripts
+    }
+
+    pub fn statuses(&self) -> Vec<ScriptStatus> {
+        self.scripts
+            .iter()
+            .map(|script| ScriptStatus {
+                name: script.name.clone(),
+                error: script.error.lock().ok().and_then(|error| error.clone()),
+            })
+            .collect()
+    }
+
+    /// Scripts defining a capture hook, in order
+    pub fn capture_hooks(&self) -> CaptureHooks {
+        CaptureHooks {
+            engine: self.engine.clone(),
+            scripts: self
+                .scripts
+                .iter()
+                .filter(|script| script.defines(CAPTURE_HOOK))
+                .map(|script| CaptureScript {
+                    name: script.name.clone(),
+                    ast: script.ast.clone().unwrap(),
+                    error: script.error.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Scripts usable as transformations
+    pub fn transforms(&self) -> Vec<ScriptTransform> {
+        self.scripts
+            .iter()
+            .filter(|script| script.defines(TRANSFORM_HOOK))
+            .map(|script| ScriptTransform {
+                name: script.name.clone(),
+                engine: self.engine.clone(),
+                ast: script.ast.clone().unwrap(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CaptureScript {
+    name: String,
+    ast: Arc<AST>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl CaptureScript {
+    fn set_error(&self, error: Option<String>) {
+        if let Ok(mut current) = self.error.lock() {
+            *current = error;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureHooks {
+    engine: Arc<Engine>,
+    scripts: Vec<CaptureScript>,
+}
+
+impl CaptureHooks {
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Runs the capture hooks in order, `None` when one of them vetoed the capture.
+    /// A failing hook is skipped and its error recorded.
+    pub fn run(&self, mut entry: ScriptEntry) -> Option<ScriptEntry> {
+        for script in &self.scripts {
+            match call_hook(&self.engine, &script.ast, CAPTURE_HOOK, &entry) {
+                Ok((result, this)) => {
+                    if let Err(e) = entry.update_from(this) {
+                        tracing::error!("Script {} returned an invalid entry\n{e:?}", script.name);
+                        script.set_error(Some(e.to_string()));
+                        continue;
+                    }
+                    // Only the error of the last call is shown
+                    script.set_error(None);
+                    if result.as_bool() == Ok(false) {
+                        return None;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Script {} failed on capture\n{e:?}", script.name);
+                    script.set_error(Some(e.to_string()));
+                }
+            }
+        }
+        Some(entry)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptTransform {
+    pub name: String,
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+}
+
+impl ScriptTransform {
+    pub fn apply(&self, text: &str) -> anyhow::Result<String> {
+        let mut entry = ScriptEntry {
+            text: text.to_owned(),
+            ..Default::default()
+        };
+        let (result, this) = call_hook(&self.engine, &self.ast, TRANSFORM_HOOK, &entry)?;
+        if result.is_string() {
+            return Ok(result.into_string().unwrap());
+        }
+        entry.update_from(this)?;
+        Ok(entry.text)
+    }
+}
+
+/// Calls the hook with the entry bound to `this`, returns its result and the updated `this`
+fn call_hook(
+    engine: &Engine,
+    ast: &AST,
+    hook: &str,
+    entry: &ScriptEntry,
+) -> anyhow::Result<(Dynamic, Dynamic)> {
+    let mut this = entry.to_dynamic();
+    let result = engine.call_fn_with_options::<Dynamic>(
+        CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut this),
+        &mut Scope::new(),
+        ast,
+        hook,
+        (),
+    )?;
+    Ok((result, this))
+}
+
+/// Engine without access to the file system or other modules, with bounded resources
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(10_000_000);
+    engine.set_max_array_size(100_000);
+    engine.set_max_map_size(10_000);
+    engine.on_print(|message| tracing::info!("Script: {message}"));
+    engine.on_debug(|message, source, position| {
+        tracing::debug!(
+            "Script {}@{position}: {message}",
+            source.unwrap_or_default()
+        )
+    });
+    engine
+}
+
+pub fn scripts_dir() -> anyhow::Result<PathBuf> {
+    let mut scripts_dir = project_dirs()?.config_dir().to_path_buf();
+    scripts_dir.push(SCRIPTS_DIR);
+    Ok(scripts_dir)
+}
+
+fn script_paths() -> anyhow::Result<Vec<PathBuf>> {
+    let scripts_dir = scripts_dir()?;
+    if !scripts_dir.try_exists()? {
+        fs::create_dir_all(&scripts_dir)?;
+    }
+    let mut paths = fs::read_dir(scripts_dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == SCRIPT_EXTENSION)
+    });
+    paths.sort();
+    Ok(paths)
+}
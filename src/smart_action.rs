@@ -0,0 +1,251 @@
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::{
+    classify::ContentKind,
+    color::{self, ColorFormat},
+    db::Item,
+};
+
+/// Action offered for an entry depending on the kinds of content detected in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SmartAction {
+    OpenUrl,
+    ComposeEmail,
+    OpenPath,
+    /// Opens the folder containing the path
+    RevealFile,
+    PrettyPrintJson,
+    MinifyJson,
+    /// Unix timestamp, in seconds or milliseconds, to a local date
+    ConvertTimestamp,
+    ConvertColor(ColorFormat),
+}
+
+/// Result of an action, built-ins produce text to paste while the others launch an app
+#[derive(Debug)]
+pub enum ActionOutput {
+    Text(String),
+    Launched,
+}
+
+/// Key pressed along with Alt in the history window to run an action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionKey {
+    pub action: SmartAction,
+    pub key: char,
+}
+
+impl SmartAction {
+    pub const ALL: [SmartAction; 11] = [
+        SmartAction::OpenUrl,
+        SmartAction::ComposeEmail,
+        SmartAction::OpenPath,
+        SmartAction::RevealFile,
+        SmartAction::PrettyPrintJson,
+        SmartAction::MinifyJson,
+        SmartAction::ConvertTimestamp,
+        SmartAction::ConvertColor(ColorFormat::Hex),
+        SmartAction::ConvertColor(ColorFormat::Rgb),
+        SmartAction::ConvertColor(ColorFormat::Hsl),
+        SmartAction::ConvertColor(ColorFormat::Oklch),
+    ];
+
+    /// Actions applicable to the entry, in menu order
+    pub fn for_item(item: &Item) -> Vec<SmartAction> {
+        let text = item.entry.data.trim();
+        item.kinds()
+            .into_iter()
+            .flat_map(|kind| match kind {
+                ContentKind::Url => vec![SmartAction::OpenUrl],
+                ContentKind::Email => vec![SmartAction::ComposeEmail],
+                ContentKind::Path => vec![SmartAction::OpenPath, SmartAction::RevealFile],
+                ContentKind::Json => vec![SmartAction::PrettyPrintJson, SmartAction::MinifyJson],
+                ContentKind::Number if parse_timestamp(text).is_some() => {
+                    vec![SmartAction::ConvertTimestamp]
+                }
+                ContentKind::Color => match color::parse(text) {
+                    Some((_, current)) => ColorFormat::ALL
+                        .into_iter()
+                        .filter(|format| *format != current)
+                        .map(SmartAction::ConvertColor)
+                        .collect(),
+                    None => Vec::new(),
+                },
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    pub async fn run(self, text: &str) -> anyhow::Result<ActionOutput> {
+        let text = text.trim();
+        match self {
+            SmartAction::OpenUrl => {
+                let url = if text.contains("://") {
+                    text.to_owned()
+                } else {
+                    format!("https://{text}")
+                };
+                launch(open_command(url.as_ref())).await
+            }
+            SmartAction::ComposeEmail => {
+                let mailto = if text.starts_with("mailto:") {
+                    text.to_owned()
+                } else {
+                    format!("mailto:{text}")
+                };
+                launch(open_command(mailto.as_ref())).await
+            }
+            SmartAction::OpenPath => launch(open_command(expand_home(text).as_os_str())).await,
+            SmartAction::RevealFile => launch(reveal_command(&expand_home(text))).await,
+            SmartAction::PrettyPrintJson => Ok(ActionOutput::Text(serde_json::to_string_pretty(
+                &serde_json::from_str::<serde_json::Value>(text)?,
+            )?)),
+            SmartAction::MinifyJson => Ok(ActionOutput::Text(serde_json::to_string(
+                &serde_json::from_str::<serde_json::Value>(text)?,
+            )?)),
+            SmartAction::ConvertTimestamp => {
+                let date = parse_timestamp(text)
+                    .ok_or(anyhow::anyhow!("{text} is not a timestamp"))?
+                    .with_timezone(&Local);
+                Ok(ActionOutput::Text(
+                    date.format("%Y-%m-%d %H:%M:%S").to_string(),
+                ))
+            }
+            SmartAction::ConvertColor(format) => {
+                let (color, _) =
+                    color::parse(text).ok_or(anyhow::anyhow!("{text} is not a color"))?;
+                Ok(ActionOutput::Text(format.format(color)))
+            }
+        }
+    }
+
+    /// Menu label, conversions show their result
+    pub fn label(self, text: &str) -> String {
+        match self {
+            SmartAction::ConvertColor(format) => match color::parse(text) {
+                Some((color, _)) => format!("Paste as {}", format.format(color)),
+                None => self.to_string(),
+            },
+            _ => self.to_string(),
+        }
+    }
+
+    pub fn default_keys() -> Vec<ActionKey> {
+        [
+            (SmartAction::OpenUrl, 'o'),
+            (SmartAction::ComposeEmail, 'o'),
+            (SmartAction::OpenPath, 'o'),
+            (SmartAction::RevealFile, 'r'),
+            (SmartAction::PrettyPrintJson, 'p'),
+            (SmartAction::MinifyJson, 'm'),
+            (SmartAction::ConvertTimestamp, 'd'),
+        ]
+        .into_iter()
+        .map(|(action, key)| ActionKey { action, key })
+        .collect()
+    }
+}
+
+impl Display for SmartAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmartAction::OpenUrl => f.write_str("Open in browser"),
+            SmartAction::ComposeEmail => f.write_str("Compose email"),
+            SmartAction::OpenPath => f.write_str("Open"),
+            SmartAction::RevealFile => f.write_str("Reveal in file manager"),
+            SmartAction::PrettyPrintJson => f.write_str("Pretty-print JSON"),
+            SmartAction::MinifyJson => f.write_str("Minify JSON"),
+            SmartAction::ConvertTimestamp => f.write_str("Convert timestamp to date"),
+            SmartAction::ConvertColor(format) => write!(f, "Paste color as {format}"),
+        }
+    }
+}
+
+/// Plain integers of 9 to 10 digits are seconds, 12 to 13 digits milliseconds,
+/// which covers dates from 1973 to 2286
+fn parse_timestamp(text: &str) -> Option<DateTime<chrono::Utc>> {
+    if !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let value = text.parse::<i64>().ok()?;
+    match text.len() {
+        9 | 10 => DateTime::from_timestamp(value, 0),
+        12 | 13 => DateTime::from_timestamp_millis(value),
+        _ => None,
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ if path == "~" => BaseDirs::new()
+            .map(|dirs| dirs.home_dir().to_path_buf())
+            .unwrap_or_else(|| path.into()),
+        _ => path.into(),
+    }
+}
+
+/// Waits for the opener only, apps it starts outlive it
+async fn launch(mut command: Command) -> anyhow::Result<ActionOutput> {
+    let status = command.status().await?;
+    if !status.success() {
+        anyhow::bail!("Exited with {status}");
+    }
+    Ok(ActionOutput::Launched)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn open_command(target: &std::ffi::OsStr) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(target);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn open_command(target: &std::ffi::OsStr) -> Command {
+    let mut command = Command::new("open");
+    command.arg(target);
+    command
+}
+
+#[cfg(windows)]
+fn open_command(target: &std::ffi::OsStr) -> Command {
+    let mut command = Command::new("explorer");
+    command.arg(target);
+    command
+}
+
+/// File managers opened through `xdg-open` cannot select a file, its folder is opened instead
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reveal_command(path: &Path) -> Command {
+    let folder = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(path)
+    };
+    open_command(folder.as_os_str())
+}
+
+#[cfg(target_os = "macos")]
+fn reveal_command(path: &Path) -> Command {
+    let mut command = Command::new("open");
+    command.arg("-R").arg(path);
+    command
+}
+
+#[cfg(windows)]
+fn reveal_command(path: &Path) -> Command {
+    let mut command = Command::new("explorer");
+    let mut select = std::ffi::OsString::from("/select,");
+    select.push(path);
+    command.arg(select);
+    command
+}
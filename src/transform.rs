@@ -0,0 +1,505 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::hotkey::GlobalShortcut;
+
+/// Built-in text transformations applicable to an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Transform {
+    Uppercase,
+    Lowercase,
+    TitleCase,
+    SnakeCase,
+    CamelCase,
+    Trim,
+    CollapseWhitespace,
+    SortLines,
+    UniqueLines,
+    ReverseLines,
+    UrlEncode,
+    UrlDecode,
+    Base64Encode,
+    Base64Decode,
+    HtmlEncode,
+    HtmlDecode,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 16] = [
+        Transform::Uppercase,
+        Transform::Lowercase,
+        Transform::TitleCase,
+        Transform::SnakeCase,
+        Transform::CamelCase,
+        Transform::Trim,
+        Transform::CollapseWhitespace,
+        Transform::SortLines,
+        Transform::UniqueLines,
+        Transform::ReverseLines,
+        Transform::UrlEncode,
+        Transform::UrlDecode,
+        Transform::Base64Encode,
+        Transform::Base64Decode,
+        Transform::HtmlEncode,
+        Transform::HtmlDecode,
+    ];
+
+    pub fn apply(self, text: &str) -> anyhow::Result<String> {
+        Ok(match self {
+            Transform::Uppercase => text.to_uppercase(),
+            Transform::Lowercase => text.to_lowercase(),
+            Transform::TitleCase => map_lines(text, title_case),
+            Transform::SnakeCase => map_lines(text, |line| words(line).join("_")),
+            Transform::CamelCase => map_lines(text, camel_case),
+            Transform::Trim => text.trim().to_owned(),
+            Transform::CollapseWhitespace => text.split_whitespace().collect::<Vec<_>>().join(" "),
+            Transform::SortLines => {
+                let mut lines = text.lines().collect::<Vec<_>>();
+                lines.sort_unstable();
+                lines.join("\n")
+            }
+            Transform::UniqueLines => {
+                let mut seen = HashSet::new();
+                text.lines()
+                    .filter(|line| seen.insert(*line))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            Transform::ReverseLines => text.lines().rev().collect::<Vec<_>>().join("\n"),
+            Transform::UrlEncode => utf8_percent_encode(text, NON_ALPHANUMERIC).to_string(),
+            Transform::UrlDecode => percent_decode_str(&text.replace('+', " "))
+                .decode_utf8()?
+                .into_owned(),
+            Transform::Base64Encode => STANDARD.encode(text),
+            Transform::Base64Decode => String::from_utf8(STANDARD.decode(text.trim())?)?,
+            Transform::HtmlEncode => html_encode(text),
+            Transform::HtmlDecode => html_decode(text),
+        })
+    }
+}
+
+impl Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Transform::Uppercase => "UPPERCASE",
+            Transform::Lowercase => "lowercase",
+            Transform::TitleCase => "Title Case",
+            Transform::SnakeCase => "snake_case",
+            Transform::CamelCase => "camelCase",
+            Transform::Trim => "Trim",
+            Transform::CollapseWhitespace => "Collapse whitespace",
+            Transform::SortLines => "Sort lines",
+            Transform::UniqueLines => "Unique lines",
+            Transform::ReverseLines => "Reverse lines",
+            Transform::UrlEncode => "URL encode",
+            Transform::UrlDecode => "URL decode",
+            Transform::Base64Encode => "Base64 encode",
+            Transform::Base64Decode => "Base64 decode",
+            Transform::HtmlEncode => "HTML encode",
+            Transform::HtmlDecode => "HTML decode",
+        })
+    }
+}
+
+/// Regex of a replace step, compiled once when the pipeline is loaded or edited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct ReplacePattern {
+    source: String,
+    regex: Result<Regex, regex::Error>,
+}
+
+impl ReplacePattern {
+    pub fn new(source: String) -> Self {
+        let regex = Regex::new(&source);
+        Self { source, regex }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn error(&self) -> Option<&regex::Error> {
+        self.regex.as_ref().err()
+    }
+}
+
+/// Used when loading the config
+impl From<String> for ReplacePattern {
+    fn from(source: String) -> Self {
+        let pattern = ReplacePattern::new(source);
+        if let Some(e) = pattern.error() {
+            tracing::error!("Invalid replace pattern {}\n{e:?}", pattern.source);
+        }
+        pattern
+    }
+}
+
+impl From<ReplacePattern> for String {
+    fn from(pattern: ReplacePattern) -> Self {
+        pattern.source
+    }
+}
+
+impl PartialEq for ReplacePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for ReplacePattern {}
+
+/// A single stage of a user-defined pipeline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Step {
+    Builtin(Transform),
+    /// Regex find and replace, `$1` or `${name}` refer to capture groups
+    Replace {
+        pattern: ReplacePattern,
+        replacement: String,
+    },
+    PrefixLines(String),
+    SuffixLines(String),
+    /// Surrounds the whole text with the same string on both sides
+    Wrap(String),
+    JoinLines(String),
+}
+
+impl Step {
+    pub fn presets() -> Vec<Step> {
+        let mut presets = vec![
+            Step::Replace {
+                pattern: ReplacePattern::new(String::new()),
+                replacement: String::new(),
+            },
+            Step::PrefixLines(String::new()),
+            Step::SuffixLines(String::new()),
+            Step::Wrap("\"".into()),
+            Step::JoinLines(", ".into()),
+        ];
+        presets.extend(Transform::ALL.map(Step::Builtin));
+        presets
+    }
+
+    pub fn apply(&self, text: &str) -> anyhow::Result<String> {
+        Ok(match self {
+            Step::Builtin(transform) => transform.apply(text)?,
+            Step::Replace {
+                pattern,
+                replacement,
+            } => pattern
+                .regex
+                .as_ref()
+                .map_err(Clone::clone)?
+                .replace_all(text, replacement.as_str())
+                .into_owned(),
+            Step::PrefixLines(prefix) => map_lines(text, |line| format!("{prefix}{line}")),
+            Step::SuffixLines(suffix) => map_lines(text, |line| format!("{line}{suffix}")),
+            Step::Wrap(wrapper) => format!("{wrapper}{text}{wrapper}"),
+            Step::JoinLines(separator) => text.lines().collect::<Vec<_>>().join(separator),
+        })
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Builtin(transform) => transform.fmt(f),
+            Step::Replace { .. } => f.write_str("Regex replace"),
+            Step::PrefixLines(_) => f.write_str("Prefix lines"),
+            Step::SuffixLines(_) => f.write_str("Suffix lines"),
+            Step::Wrap(_) => f.write_str("Wrap"),
+            Step::JoinLines(_) => f.write_str("Join lines"),
+        }
+    }
+}
+
+/// Named sequence of steps, each one fed the output of the previous
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pipeline {
+    pub name: String,
+    pub steps: Vec<Step>,
+    /// Transforms the clipboard in place
+    pub shortcut: Option<GlobalShortcut>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self {
+            name: "New pipeline".into(),
+            steps: Vec::new(),
+            shortcut: None,
+        }
+    }
+}
+
+impl Pipeline {
+    pub fn apply(&self, text: &str) -> anyhow::Result<String> {
+        self.steps
+            .iter()
+            .try_fold(text.to_owned(), |text, step| step.apply(&text))
+    }
+}
+
+fn map_lines(text: &str, transform: impl Fn(&str) -> String) -> String {
+    text.lines().map(transform).collect::<Vec<_>>().join("\n")
+}
+
+/// Lowercase words of an identifier or sentence, split on separators and case changes.
+/// An acronym ends before its last capital when a lowercase letter follows, as in `HTTPServer`.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    let mut previous = None::<char>;
+    while let Some(c) = chars.next() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous = None;
+            continue;
+        }
+        let next_lowercase = chars.peek().is_some_and(|next| next.is_lowercase());
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next_lowercase)
+            });
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous = Some(c);
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn title_case(line: &str) -> String {
+    line.split(' ')
+        .map(|word| capitalize(&word.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn camel_case(line: &str) -> String {
+    words(line)
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            if index == 0 {
+                word.clone()
+            } else {
+                capitalize(word)
+            }
+        })
+        .collect()
+}
+
+fn html_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => encoded.push_str("&amp;"),
+            '<' => encoded.push_str("&lt;"),
+            '>' => encoded.push_str("&gt;"),
+            '"' => encoded.push_str("&quot;"),
+            '\'' => encoded.push_str("&#39;"),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+/// Decodes the common named entities and numeric ones, unknown entities are kept as is
+fn html_decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest
+            .find(';')
+            .map(|end| (&rest[1..end], end))
+            .and_then(|(name, end)| decode_entity(name).map(|c| (c, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = match name.strip_prefix('#')? {
+                hex if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok()?,
+                decimal => decimal.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(transform: Transform, text: &str) -> String {
+        transform.apply(text).unwrap()
+    }
+
+    #[test]
+    fn changes_case() {
+        assert_eq!(apply(Transform::Uppercase, "straße"), "STRASSE");
+        assert_eq!(apply(Transform::Lowercase, "ÉTÉ"), "été");
+        assert_eq!(
+            apply(Transform::TitleCase, "élan VITAL\nsecond line"),
+            "Élan Vital\nSecond Line"
+        );
+    }
+
+    #[test]
+    fn splits_identifiers_into_words() {
+        assert_eq!(
+            apply(Transform::SnakeCase, "parseHTTPResponse"),
+            "parse_http_response"
+        );
+        assert_eq!(
+            apply(Transform::SnakeCase, "version2Update"),
+            "version2_update"
+        );
+        assert_eq!(
+            apply(Transform::SnakeCase, "Hello, world-foo"),
+            "hello_world_foo"
+        );
+        assert_eq!(apply(Transform::SnakeCase, "ÉtéChaud"), "été_chaud");
+        assert_eq!(
+            apply(Transform::CamelCase, "XMLHttpRequest"),
+            "xmlHttpRequest"
+        );
+        assert_eq!(
+            apply(Transform::CamelCase, "user_id\nAPI key"),
+            "userId\napiKey"
+        );
+    }
+
+    #[test]
+    fn edits_lines_and_whitespace() {
+        assert_eq!(apply(Transform::Trim, "  a b \n"), "a b");
+        assert_eq!(
+            apply(Transform::CollapseWhitespace, " a \t b\n c "),
+            "a b c"
+        );
+        assert_eq!(apply(Transform::SortLines, "b\na\nb"), "a\nb\nb");
+        assert_eq!(apply(Transform::UniqueLines, "b\na\nb"), "b\na");
+        assert_eq!(apply(Transform::ReverseLines, "a\nb\nc"), "c\nb\na");
+    }
+
+    #[test]
+    fn encodes_and_decodes_urls() {
+        assert_eq!(apply(Transform::UrlEncode, "a b&é"), "a%20b%26%C3%A9");
+        assert_eq!(apply(Transform::UrlDecode, "a+b%20c%C3%A9"), "a b cé");
+        assert!(Transform::UrlDecode.apply("%FF").is_err());
+    }
+
+    #[test]
+    fn encodes_and_decodes_base64() {
+        assert_eq!(apply(Transform::Base64Encode, "héllo"), "aMOpbGxv");
+        assert_eq!(apply(Transform::Base64Decode, " aMOpbGxv\n"), "héllo");
+        assert!(Transform::Base64Decode.apply("not base64!").is_err());
+        // Valid base64 of bytes that are not UTF-8
+        assert!(Transform::Base64Decode.apply("/w==").is_err());
+    }
+
+    #[test]
+    fn encodes_html() {
+        assert_eq!(
+            apply(Transform::HtmlEncode, r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn decodes_html_entities() {
+        assert_eq!(
+            apply(Transform::HtmlDecode, "&lt;b&gt; &quot;&apos;&nbsp;"),
+            "<b> \"'\u{a0}"
+        );
+        assert_eq!(apply(Transform::HtmlDecode, "&#65;&#x42;&#X1F600;"), "AB😀");
+        // Decoded once, an encoded entity stays an entity
+        assert_eq!(apply(Transform::HtmlDecode, "&amp;amp;"), "&amp;");
+        assert_eq!(apply(Transform::HtmlDecode, "AT&T &amp; co"), "AT&T & co");
+        assert_eq!(
+            apply(Transform::HtmlDecode, "&unknown; &#xZZ; &#1114112; &"),
+            "&unknown; &#xZZ; &#1114112; &"
+        );
+    }
+
+    #[test]
+    fn replaces_with_capture_groups() {
+        let step = Step::Replace {
+            pattern: ReplacePattern::new(r"(\w+)@(?<host>\w+)".into()),
+            replacement: "${host} at $1".into(),
+        };
+        assert_eq!(step.apply("me@example").unwrap(), "example at me");
+    }
+
+    #[test]
+    fn invalid_replace_pattern_is_an_error() {
+        let pattern = ReplacePattern::new("(".into());
+        assert!(pattern.error().is_some());
+        let step = Step::Replace {
+            pattern,
+            replacement: String::new(),
+        };
+        assert!(step.apply("text").is_err());
+    }
+
+    #[test]
+    fn pipeline_feeds_each_step_the_previous_output() {
+        let pipeline = Pipeline {
+            steps: vec![
+                Step::Builtin(Transform::Trim),
+                Step::Builtin(Transform::Uppercase),
+                Step::Wrap("\"".into()),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(pipeline.apply("  hi ").unwrap(), "\"HI\"");
+    }
+}
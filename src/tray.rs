@@ -8,16 +8,20 @@ use iced::{
 use joy_macro::DisplayFromDebug;
 use tokio::sync::mpsc;
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem},
     Icon, TrayIcon, TrayIconBuilder,
 };
 
 use crate::{app::Message, utils::ASYNC_CHANNEL_SIZE, JOY_CLIPPY_ICON};
 
+const TOOLTIP: &str = "Joy Clippy";
+
 #[derive(Debug, DisplayFromDebug)]
 enum MenuEntry {
     Open,
     Settings,
+    Collect,
+    Trash,
     Quit,
 }
 
@@ -28,23 +32,56 @@ impl FromStr for MenuEntry {
         match s {
             "Open" => Ok(MenuEntry::Open),
             "Settings" => Ok(MenuEntry::Settings),
+            "Collect" => Ok(MenuEntry::Collect),
+            "Trash" => Ok(MenuEntry::Trash),
             "Quit" => Ok(MenuEntry::Quit),
             _ => Err(()),
         }
     }
 }
 
-pub fn create_tray() -> TrayIcon {
+pub struct Tray {
+    icon: TrayIcon,
+    collect_item: CheckMenuItem,
+}
+
+impl Tray {
+    pub fn update_status(&self, queued: usize, collecting: bool) {
+        let mut tooltip = TOOLTIP.to_owned();
+        if queued > 0 {
+            tooltip.push_str(&format!(" - {queued} queued"));
+        }
+        if collecting {
+            tooltip.push_str(" - collecting");
+        }
+        if let Err(e) = self.icon.set_tooltip(Some(tooltip)) {
+            tracing::error!("Could not update tray tooltip\n{e:?}");
+        }
+        self.collect_item.set_checked(collecting);
+    }
+}
+
+pub fn create_tray() -> Tray {
     let icon_data = load_from_memory(JOY_CLIPPY_ICON).unwrap();
     let (width, height) = (icon_data.width(), icon_data.height());
     let icon = Icon::from_rgba(icon_data.into_bytes(), width, height).unwrap();
 
-    TrayIconBuilder::new()
-        .with_tooltip("Joy Clippy")
+    let collect_item = CheckMenuItem::with_id(
+        MenuEntry::Collect.to_string(),
+        "Collect mode",
+        true,
+        false,
+        None,
+    );
+
+    let icon = TrayIconBuilder::new()
+        .with_tooltip(TOOLTIP)
         .with_menu(Box::new(
             Menu::with_items(&[
                 &MenuItem::with_id(MenuEntry::Open.to_string(), "Open", true, None),
                 &MenuItem::with_id(MenuEntry::Settings.to_string(), "Settings", true, None),
+                &collect_item,
+                &MenuItem::with_id(MenuEntry::Trash.to_string(), "Trash", true, None),
                 &PredefinedMenuItem::separator(),
                 &MenuItem::with_id(MenuEntry::Quit.to_string(), "Quit", true, None),
             ])
@@ -52,7 +89,9 @@ pub fn create_tray() -> TrayIcon {
         ))
         .with_icon(icon)
         .build()
-        .unwrap()
+        .unwrap();
+
+    Tray { icon, collect_item }
 }
 
 pub fn subscribe_tray_menu_event() -> impl Stream<Item = Message> {
@@ -75,6 +114,8 @@ pub fn subscribe_tray_menu_event() -> impl Stream<Item = Message> {
                     MenuEntry::Open => Message::RequestOpenHistoryWindow,
                     MenuEntry::Quit => Message::ExitApp,
                     MenuEntry::Settings => Message::OpenSettingsWindow,
+                    MenuEntry::Collect => Message::ToggleCollectMode,
+                    MenuEntry::Trash => Message::OpenTrashWindow,
                 };
                 output.send(message).await.unwrap();
             }
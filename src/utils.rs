@@ -1,9 +1,18 @@
-use iced::{keyboard::key, Color, Event};
+use directories::ProjectDirs;
+use iced::{
+    keyboard::{key, Modifiers},
+    Color, Event,
+};
 
-use crate::app::Shortcut;
+use crate::{app::Shortcut, APPLICATION, ORGANIZATION, QUALIFIER};
 
 pub const ASYNC_CHANNEL_SIZE: usize = 10;
 
+pub fn project_dirs() -> anyhow::Result<ProjectDirs> {
+    ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION)
+        .ok_or(anyhow::anyhow!("Could not get os dirs"))
+}
+
 pub trait ColorUtils {
     fn darken(self, percentage: f32) -> Self;
     fn lighten(self, percentage: f32) -> Self;
@@ -169,3 +178,21 @@ pub fn iced_event_to_shortcut(event: iced::Event) -> Option<Shortcut> {
         _ => None,
     }
 }
+
+pub fn shortcut_to_chord(shortcut: &Shortcut) -> Vec<rdev::Key> {
+    let mut chord = Vec::new();
+    for (modifier, key) in [
+        (Modifiers::CTRL, rdev::Key::ControlLeft),
+        (Modifiers::SHIFT, rdev::Key::ShiftLeft),
+        (Modifiers::ALT, rdev::Key::Alt),
+        (Modifiers::LOGO, rdev::Key::MetaLeft),
+    ] {
+        if shortcut.modifiers.contains(modifier) {
+            chord.push(key);
+        }
+    }
+    if !chord.contains(&shortcut.rdev_key) {
+        chord.push(shortcut.rdev_key);
+    }
+    chord
+}
@@ -0,0 +1,129 @@
+use std::fmt::{self, Display};
+
+use iced::{
+    keyboard::{self, key},
+    widget::{
+        button, column, horizontal_space, pick_list, row, text, text_editor, text_editor::Binding,
+    },
+    Alignment, Element, Length, Task,
+};
+
+use crate::{app, db::Item};
+
+#[derive(Debug)]
+pub struct State {
+    item: Item,
+    content: text_editor::Content,
+    revisions: Vec<Revision>,
+}
+
+/// Previous text of the entry, saved each time it is edited
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision(pub entity::entry_revision::Model);
+
+impl Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.edited_at.format("%Y-%m-%d %H:%M:%S"))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Edit(text_editor::Action),
+    RevisionsLoaded(Vec<Revision>),
+    RevisionSelected(Revision),
+    Save,
+    PasteOnce,
+    Cancel,
+}
+
+impl State {
+    pub fn new(item: Item) -> State {
+        State {
+            content: text_editor::Content::with_text(&item.entry.data),
+            item,
+            revisions: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        match message {
+            Message::Edit(action) => {
+                self.content.perform(action);
+                Task::none()
+            }
+            Message::RevisionsLoaded(revisions) => {
+                self.revisions = revisions;
+                Task::none()
+            }
+            Message::RevisionSelected(revision) => {
+                self.content = text_editor::Content::with_text(&revision.0.data);
+                Task::none()
+            }
+            Message::Save => Task::done(app::Message::CloseEditorWindow).chain(Task::done(
+                app::Message::SaveEntryEdit(self.item.entry.id, self.text()),
+            )),
+            Message::PasteOnce => Task::done(app::Message::CloseEditorWindow)
+                .chain(Task::done(app::Message::PasteText(self.text()))),
+            Message::Cancel => Task::done(app::Message::CloseEditorWindow),
+        }
+    }
+
+    /// `text_editor` always ends the content with a line break, it is not part of the entry
+    fn text(&self) -> String {
+        let mut text = self.content.text();
+        if text.ends_with('\n') && !self.item.entry.data.ends_with('\n') {
+            text.pop();
+        }
+        text
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        column![
+            row![
+                text!("Edit entry").size(20),
+                horizontal_space(),
+                pick_list(
+                    self.revisions.as_slice(),
+                    None::<Revision>,
+                    Message::RevisionSelected
+                )
+                .placeholder(if self.revisions.is_empty() {
+                    "No previous version"
+                } else {
+                    "Previous versions"
+                }),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            text_editor(&self.content)
+                .on_action(Message::Edit)
+                // The focused editor captures these keys before the app shortcuts see them
+                .key_binding(|key_press| match key_press.key.as_ref() {
+                    keyboard::Key::Character("s") if key_press.modifiers.command() => {
+                        Some(Binding::Custom(Message::Save))
+                    }
+                    keyboard::Key::Named(key::Named::Enter) if key_press.modifiers.command() => {
+                        Some(Binding::Custom(Message::PasteOnce))
+                    }
+                    keyboard::Key::Named(key::Named::Escape) => {
+                        Some(Binding::Custom(Message::Cancel))
+                    }
+                    _ => Binding::from_key_press(key_press),
+                })
+                .height(Length::Fill),
+            row![
+                text!("Ctrl+S: save · Ctrl+Enter: paste once · Esc: cancel").size(11),
+                horizontal_space(),
+                button(text!("Cancel")).on_press(Message::Cancel),
+                button(text!("Paste once")).on_press(Message::PasteOnce),
+                button(text!("Save")).on_press(Message::Save),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(8)
+        .padding(16)
+        .into()
+    }
+}
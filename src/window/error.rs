@@ -0,0 +1,35 @@
+use iced::{
+    widget::{button, column, container, horizontal_space, row, scrollable, text},
+    Element, Length,
+};
+
+use crate::app;
+
+/// Reports a failure of something the user started, when no other window can show it
+#[derive(Debug)]
+pub struct State {
+    pub message: String,
+}
+
+impl State {
+    pub fn new(message: String) -> State {
+        State { message }
+    }
+
+    pub fn view(&self, id: iced::window::Id) -> Element<app::Message> {
+        container(
+            column![
+                scrollable(text(self.message.as_str()).size(13)).height(Length::Fill),
+                row![
+                    horizontal_space(),
+                    button(text!("Close")).on_press(app::Message::RequestWindowClose(id)),
+                ],
+            ]
+            .spacing(8),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
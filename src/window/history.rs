@@ -1,65 +1,1017 @@
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
 use iced::{
-    widget::{button, center, column, container, horizontal_space, row, scrollable, text, Column},
-    Alignment, Element, Length, Task,
+    alignment, border,
+    keyboard::Modifiers,
+    widget::{
+        button, center, column, container, horizontal_rule, horizontal_space, image, markdown,
+        mouse_area, rich_text, row, scrollable, span, text, text_input, Column, Container, Space,
+    },
+    Alignment, ContentFit, Element, Font, Length, Task,
 };
+use tokio::time::sleep;
 
 use crate::{
     app::{self},
+    classify::ContentKind,
+    clipboard::ClipboardContent,
+    color,
+    db::Item,
+    highlight,
+    plugin::{PluginPreview, PluginTransform},
+    scripting::ScriptTransform,
+    smart_action::SmartAction,
+    transform::{Pipeline, Transform},
     utils::ColorUtils,
 };
 
+const UNDO_TOAST_DURATION: Duration = Duration::from_secs(5);
+const CODE_PREVIEW_HEIGHT: f32 = 200.0;
+const IMAGE_PREVIEW_HEIGHT: f32 = 150.0;
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "bmp", "webp", "ico"];
+
 #[derive(Debug)]
 pub enum State {
     Loaded {
         selected_item_cursor: i32,
-        items: Vec<entity::entry::Model>,
+        /// Entries matching the search query
+        items: Vec<Item>,
+        all_items: Vec<Item>,
+        query: String,
+        /// Entry ids queued for sequential paste, in paste order
+        queue: Vec<i32>,
+        /// Entry ids picked for bulk actions, the entry under the cursor is used when empty
+        selection: HashSet<i32>,
+        /// Row a Shift selection extends from
+        selection_anchor: i32,
+        modifiers: Modifiers,
+        tag_input: String,
+        /// Entry ids of the last deletion, offered for undo until the toast times out
+        undo_delete: Option<Vec<i32>>,
+        /// Incremented on each deletion so only the last timeout hides the toast
+        undo_generation: u64,
+        palette: Option<TransformPalette>,
+        action_menu: Option<ActionMenu>,
+        previewers: Vec<PluginPreview>,
+        /// Plugin previews of the entry under the cursor, along with its id, empty until the
+        /// plugins are done rendering
+        plugin_preview: Option<(i32, String)>,
+        /// Highlighted lines of the code entry under the cursor, along with its id
+        code_preview: Option<(i32, Vec<Vec<highlight::Span>>)>,
+        /// Shows the entry under the cursor in full next to the list
+        preview_pane: bool,
+        /// Parsed markdown entry under the cursor, along with its id, only with the preview pane
+        markdown_preview: Option<(i32, Vec<markdown::Item>)>,
+        /// Image the entry under the cursor holds or points to, along with its id, only with
+        /// the preview pane
+        image_preview: Option<(i32, Option<image::Handle>)>,
+        /// Lists every key binding in the footer instead of the paste ones
+        show_help: bool,
     },
     Loading,
 }
 
+/// Filterable list of transformations applied to the entry under the cursor
+#[derive(Debug, Default)]
+pub struct TransformPalette {
+    filter: String,
+    cursor: usize,
+    /// User-defined actions, listed before the built-ins
+    actions: Vec<PaletteAction>,
+    /// Selected action applied to the entry under the cursor, along with the entry id, filter
+    /// and cursor it was computed for
+    preview: Option<((i32, String, usize), Result<String, String>)>,
+}
+
+impl TransformPalette {
+    fn matches(&self) -> Vec<PaletteAction> {
+        let filter = self.filter.to_lowercase();
+        self.actions
+            .iter()
+            .cloned()
+            .chain(Transform::ALL.map(PaletteAction::Builtin))
+            .filter(|action| action.to_string().to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    fn selected(&self) -> Option<PaletteAction> {
+        self.matches().into_iter().nth(self.cursor)
+    }
+
+    /// Applies the selected action when the entry, filter or cursor changed
+    fn render_preview(&mut self, item: Option<&Item>) {
+        let key = item.map(|item| (item.entry.id, self.filter.clone(), self.cursor));
+        if self
+            .preview
+            .as_ref()
+            .is_some_and(|(computed_for, _)| Some(computed_for) == key.as_ref())
+        {
+            return;
+        }
+        self.preview = key.zip(item).and_then(|(key, item)| {
+            let transformed = self
+                .selected()?
+                .apply(&item.entry.data)
+                .map_err(|e| e.to_string());
+            Some((key, transformed))
+        });
+    }
+}
+
+/// Smart actions applicable to the entry under the cursor
+#[derive(Debug)]
+pub struct ActionMenu {
+    cursor: usize,
+    actions: Vec<SmartAction>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PaletteAction {
+    Builtin(Transform),
+    Pipeline(Pipeline),
+    Script(ScriptTransform),
+    Plugin(PluginTransform),
+}
+
+impl PaletteAction {
+    fn apply(&self, text: &str) -> anyhow::Result<String> {
+        match self {
+            PaletteAction::Builtin(transform) => transform.apply(text),
+            PaletteAction::Pipeline(pipeline) => pipeline.apply(text),
+            PaletteAction::Script(script) => script.apply(text),
+            PaletteAction::Plugin(transform) => transform.apply(text),
+        }
+    }
+}
+
+impl Display for PaletteAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteAction::Builtin(transform) => transform.fmt(f),
+            PaletteAction::Pipeline(pipeline) => write!(f, "{} (pipeline)", pipeline.name),
+            PaletteAction::Script(script) => write!(f, "{} (script)", script.name),
+            PaletteAction::Plugin(transform) => {
+                write!(f, "{} ({})", transform.name, transform.plugin)
+            }
+        }
+    }
+}
+
+fn palette_input_id() -> text_input::Id {
+    text_input::Id::new("transform-palette")
+}
+
+fn search_input_id() -> text_input::Id {
+    text_input::Id::new("history-search")
+}
+
+/// Every whitespace separated term of the query must match: `type:kind` matches a content
+/// kind or a language, `#tag` a tag and anything else the text, ignoring case
+fn matches_query(item: &Item, query: &str) -> bool {
+    query.split_whitespace().all(|term| {
+        let term = term.to_lowercase();
+        if let Some(kind) = term.strip_prefix("type:") {
+            item.kinds().iter().any(|k| k.name() == kind)
+                || item
+                    .entry
+                    .language
+                    .as_deref()
+                    .is_some_and(|language| language == kind)
+        } else if let Some(tag) = term.strip_prefix('#') {
+            item.tags.iter().any(|t| t.to_lowercase() == tag)
+        } else {
+            item.entry.data.to_lowercase().contains(&term)
+        }
+    })
+}
+
+fn filter_items(items: &[Item], query: &str) -> Vec<Item> {
+    items
+        .iter()
+        .filter(|item| matches_query(item, query))
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PasteMode {
+    /// Pastes every representation the entry was copied with
+    Formatted,
+    PlainText,
+    /// Types the entry key by key, for targets ignoring the paste shortcut
+    TypeOut,
+}
+
+/// Applied by the app to every targeted entry at once
+#[derive(Debug, Clone)]
+pub enum BulkAction {
+    Delete(Vec<i32>),
+    SetPinned(Vec<i32>, bool),
+    Tag(Vec<i32>, String),
+    /// Ids are in join order
+    Merge(Vec<i32>),
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     MoveHistoryCursor(i32),
-    Paste,
+    ItemClicked(usize),
+    ToggleSelected,
+    SelectAll,
+    /// Clears the selection, or closes the window when there is none
+    Cancel,
+    ModifiersChanged(Modifiers),
+    Paste(PasteMode),
+    ToggleQueued,
+    Edit,
+    /// Opens the entry in `$VISUAL` or `$EDITOR`
+    ExternalEdit,
+    Delete,
+    UndoDelete,
+    UndoTimeout(u64),
+    TogglePinned,
+    TagInputChanged(String),
+    ApplyTag,
+    Merge,
+    PasteJoined,
+    Refreshed(Vec<Item>),
+    QueryChanged(String),
+    FocusSearch,
     OpenSettings,
+    OpenTrash,
+    /// Opens the transformation palette, listing the given actions before the built-ins
+    OpenPalette(Vec<PaletteAction>),
+    PaletteFilterChanged(String),
+    PaletteClicked(usize),
+    /// Pastes the transformed entry, or saves it as a new entry when Shift is held
+    ApplyTransform,
+    OpenActionMenu,
+    ActionClicked(usize),
+    RunSelectedAction,
+    /// Runs the first of the actions bound to a key applicable to the entry under the cursor
+    RunBoundAction(Vec<SmartAction>),
+    LinkClicked(markdown::Url),
+    PluginPreviewRendered(i32, String),
+    ToggleHelp,
 }
 
 impl State {
+    pub fn loaded(
+        items: Vec<Item>,
+        queue: Vec<i32>,
+        previewers: Vec<PluginPreview>,
+        preview_pane: bool,
+    ) -> (State, Task<app::Message>) {
+        let mut state = State::Loaded {
+            selected_item_cursor: 0,
+            items: items.clone(),
+            all_items: items,
+            query: String::new(),
+            queue,
+            selection: HashSet::new(),
+            selection_anchor: 0,
+            modifiers: Modifiers::empty(),
+            tag_input: String::new(),
+            undo_delete: None,
+            undo_generation: 0,
+            palette: None,
+            action_menu: None,
+            previewers,
+            plugin_preview: None,
+            code_preview: None,
+            preview_pane,
+            markdown_preview: None,
+            image_preview: None,
+            show_help: false,
+        };
+        let task = state.render_plugin_preview();
+        state.render_code_preview();
+        state.render_markdown_preview();
+        state.render_image_preview();
+        (state, task)
+    }
+
+    pub fn cursor_item(&self) -> Option<&Item> {
+        match self {
+            State::Loaded {
+                selected_item_cursor,
+                items,
+                ..
+            } => items.get(*selected_item_cursor as usize),
+            State::Loading => None,
+        }
+    }
+
     pub fn update(&mut self, event: Message) -> Task<app::Message> {
+        let task = self.handle(event);
+        self.render_palette_preview();
+        self.render_code_preview();
+        self.render_markdown_preview();
+        self.render_image_preview();
+        task.chain(self.render_plugin_preview())
+    }
+
+    /// Renders the plugin previews off the UI thread when the entry under the cursor changed
+    fn render_plugin_preview(&mut self) -> Task<app::Message> {
+        let Self::Loaded {
+            selected_item_cursor,
+            items,
+            previewers,
+            plugin_preview,
+            ..
+        } = self
+        else {
+            return Task::none();
+        };
+        let Some(item) = items.get(*selected_item_cursor as usize) else {
+            *plugin_preview = None;
+            return Task::none();
+        };
+        if previewers.is_empty()
+            || plugin_preview
+                .as_ref()
+                .is_some_and(|(id, _)| *id == item.entry.id)
+        {
+            return Task::none();
+        }
+        let id = item.entry.id;
+        *plugin_preview = Some((id, String::new()));
+        let previewers = previewers.clone();
+        let text = item.entry.data.clone();
+        Task::perform(
+            tokio::task::spawn_blocking(move || {
+                previewers
+                    .iter()
+                    .filter_map(|previewer| {
+                        previewer
+                            .render(&text)
+                            .inspect_err(|e| {
+                                tracing::error!(
+                                    "Plugin {} failed to preview\n{e:?}",
+                                    previewer.plugin
+                                )
+                            })
+                            .ok()
+                    })
+                    .filter(|preview| !preview.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+            move |rendered| {
+                app::Message::PluginPreviewRendered(
+                    id,
+                    rendered
+                        .inspect_err(|e| tracing::error!("Plugin preview task failed\n{e:?}"))
+                        .unwrap_or_default(),
+                )
+            },
+        )
+    }
+
+    fn render_palette_preview(&mut self) {
+        if let Self::Loaded {
+            selected_item_cursor,
+            items,
+            palette: Some(palette),
+            ..
+        } = self
+        {
+            palette.render_preview(items.get(*selected_item_cursor as usize));
+        }
+    }
+
+    /// Highlights the entry under the cursor when it changed and holds code or JSON
+    fn render_code_preview(&mut self) {
+        let Self::Loaded {
+            selected_item_cursor,
+            items,
+            code_preview,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let Some(item) = items.get(*selected_item_cursor as usize) else {
+            *code_preview = None;
+            return;
+        };
+        if code_preview
+            .as_ref()
+            .is_some_and(|(id, _)| *id == item.entry.id)
+        {
+            return;
+        }
+        let language = match item.entry.language.as_deref() {
+            Some(language) => Some(language),
+            None => item.kinds().contains(&ContentKind::Json).then_some("json"),
+        };
+        *code_preview = language.map(|language| {
+            (
+                item.entry.id,
+                highlight::highlight(&item.entry.data, language),
+            )
+        });
+    }
+
+    fn render_markdown_preview(&mut self) {
+        let Self::Loaded {
+            selected_item_cursor,
+            items,
+            preview_pane: true,
+            markdown_preview,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let Some(item) = items.get(*selected_item_cursor as usize) else {
+            *markdown_preview = None;
+            return;
+        };
+        if markdown_preview
+            .as_ref()
+            .is_some_and(|(id, _)| *id == item.entry.id)
+        {
+            return;
+        }
+        *markdown_preview = item
+            .kinds()
+            .contains(&ContentKind::Markdown)
+            .then(|| (item.entry.id, markdown::parse(&item.entry.data).collect()));
+    }
+
+    fn render_image_preview(&mut self) {
+        let Self::Loaded {
+            selected_item_cursor,
+            items,
+            preview_pane: true,
+            image_preview,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let Some(item) = items.get(*selected_item_cursor as usize) else {
+            *image_preview = None;
+            return;
+        };
+        if image_preview
+            .as_ref()
+            .is_some_and(|(id, _)| *id == item.entry.id)
+        {
+            return;
+        }
+        *image_preview = Some((
+            item.entry.id,
+            image_path(item).map(image::Handle::from_path),
+        ));
+    }
+
+    fn handle(&mut self, event: Message) -> Task<app::Message> {
         match event {
             Message::MoveHistoryCursor(direction) => {
+                if let Self::Loaded {
+                    action_menu: Some(menu),
+                    ..
+                } = self
+                {
+                    let last = menu.actions.len().saturating_sub(1) as i32;
+                    menu.cursor = (menu.cursor as i32 + direction).clamp(0, last) as usize;
+                    return Task::none();
+                }
+                if let Self::Loaded {
+                    palette: Some(palette),
+                    ..
+                } = self
+                {
+                    let last = palette.matches().len().saturating_sub(1) as i32;
+                    palette.cursor = (palette.cursor as i32 + direction).clamp(0, last) as usize;
+                    return Task::none();
+                }
                 if let Self::Loaded {
                     selected_item_cursor,
                     items,
+                    selection,
+                    selection_anchor,
+                    modifiers,
+                    ..
                 } = self
                 {
                     *selected_item_cursor += direction;
+                    if *selected_item_cursor >= items.len() as i32 {
+                        *selected_item_cursor = items.len() as i32 - 1;
+                    }
                     if *selected_item_cursor < 0 {
                         *selected_item_cursor = 0
                     }
-                    if *selected_item_cursor >= items.len() as i32 {
-                        *selected_item_cursor = items.len() as i32 - 1;
+                    if modifiers.shift() {
+                        select_range(items, selection, *selection_anchor, *selected_item_cursor);
+                    } else {
+                        *selection_anchor = *selected_item_cursor;
+                        // Ctrl moves the cursor only, to pick entries with Ctrl+Space
+                        if !modifiers.control() {
+                            selection.clear();
+                        }
+                    }
+                }
+                Task::none()
+            }
+            Message::ItemClicked(index) => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    selection,
+                    selection_anchor,
+                    modifiers,
+                    ..
+                } = self
+                {
+                    *selected_item_cursor = index as i32;
+                    if modifiers.shift() {
+                        select_range(items, selection, *selection_anchor, *selected_item_cursor);
+                    } else {
+                        *selection_anchor = *selected_item_cursor;
+                        if modifiers.control() {
+                            toggle_selected(items, selection, index);
+                        } else {
+                            selection.clear();
+                        }
                     }
                 }
                 Task::none()
             }
-            Message::Paste => {
+            Message::ToggleSelected => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    selection,
+                    selection_anchor,
+                    ..
+                } = self
+                {
+                    *selection_anchor = *selected_item_cursor;
+                    toggle_selected(items, selection, *selected_item_cursor as usize);
+                }
+                Task::none()
+            }
+            Message::SelectAll => {
+                if let Self::Loaded {
+                    items, selection, ..
+                } = self
+                {
+                    *selection = items.iter().map(|item| item.entry.id).collect();
+                }
+                Task::none()
+            }
+            Message::Cancel => match self {
+                Self::Loaded { palette, .. } if palette.is_some() => {
+                    *palette = None;
+                    Task::none()
+                }
+                Self::Loaded { action_menu, .. } if action_menu.is_some() => {
+                    *action_menu = None;
+                    Task::none()
+                }
+                Self::Loaded { selection, .. } if !selection.is_empty() => {
+                    selection.clear();
+                    Task::none()
+                }
+                _ => Task::done(app::Message::RequestCloseHistoryWindow),
+            },
+            Message::ModifiersChanged(new_modifiers) => {
+                if let Self::Loaded { modifiers, .. } = self {
+                    *modifiers = new_modifiers;
+                }
+                Task::none()
+            }
+            Message::Paste(_)
+                if matches!(
+                    self,
+                    Self::Loaded {
+                        palette: Some(_),
+                        ..
+                    }
+                ) =>
+            {
+                self.update(Message::ApplyTransform)
+            }
+            Message::Paste(_)
+                if matches!(
+                    self,
+                    Self::Loaded {
+                        action_menu: Some(_),
+                        ..
+                    }
+                ) =>
+            {
+                self.update(Message::RunSelectedAction)
+            }
+            Message::Paste(mode) => {
                 if let Self::Loaded {
                     selected_item_cursor,
                     items,
+                    ..
                 } = self
                 {
-                    Task::done(app::Message::RequestPaste(
-                        items[*selected_item_cursor as usize].clone(),
+                    match items.get(*selected_item_cursor as usize) {
+                        Some(item) => Task::done(app::Message::RequestPaste(item.clone(), mode)),
+                        None => Task::none(),
+                    }
+                } else {
+                    Task::none()
+                }
+            }
+            Message::ToggleQueued => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    all_items,
+                    queue,
+                    ..
+                } = self
+                {
+                    let Some(selected) = items.get(*selected_item_cursor as usize) else {
+                        return Task::none();
+                    };
+                    match queue.iter().position(|id| *id == selected.entry.id) {
+                        Some(position) => {
+                            queue.remove(position);
+                        }
+                        None => queue.push(selected.entry.id),
+                    }
+                    let queued_items = queue
+                        .iter()
+                        .filter_map(|id| all_items.iter().find(|item| item.entry.id == *id))
+                        .cloned()
+                        .collect();
+                    Task::done(app::Message::SetPasteQueue(queued_items))
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Edit => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    ..
+                } = self
+                {
+                    match items.get(*selected_item_cursor as usize) {
+                        Some(item) => Task::done(app::Message::OpenEditorWindow(item.clone())),
+                        None => Task::none(),
+                    }
+                } else {
+                    Task::none()
+                }
+            }
+            Message::ExternalEdit => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    ..
+                } = self
+                {
+                    match items.get(*selected_item_cursor as usize) {
+                        Some(item) => Task::done(app::Message::ExternalEdit(item.clone())),
+                        None => Task::none(),
+                    }
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Delete => {
+                let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    selection,
+                    undo_delete,
+                    undo_generation,
+                    ..
+                } = self
+                else {
+                    return Task::none();
+                };
+                let ids = targets(items, selection, *selected_item_cursor)
+                    .iter()
+                    .map(|item| item.entry.id)
+                    .collect::<Vec<_>>();
+                if ids.is_empty() {
+                    return Task::none();
+                }
+                selection.clear();
+                *undo_delete = Some(ids.clone());
+                *undo_generation += 1;
+                let generation = *undo_generation;
+                Task::batch([
+                    Task::done(app::Message::BulkAction(BulkAction::Delete(ids))),
+                    Task::perform(sleep(UNDO_TOAST_DURATION), move |_| {
+                        app::Message::UndoToastTimeout(generation)
+                    }),
+                ])
+            }
+            Message::UndoDelete => match self {
+                Self::Loaded { undo_delete, .. } => match undo_delete.take() {
+                    Some(ids) => Task::done(app::Message::RestoreItems(ids)),
+                    None => Task::none(),
+                },
+                Self::Loading => Task::none(),
+            },
+            Message::UndoTimeout(generation) => {
+                if let Self::Loaded {
+                    undo_delete,
+                    undo_generation,
+                    ..
+                } = self
+                {
+                    if *undo_generation == generation {
+                        *undo_delete = None;
+                    }
+                }
+                Task::none()
+            }
+            Message::TogglePinned => self.bulk_action(|targets| {
+                let all_pinned = targets.iter().all(|item| item.entry.pinned);
+                BulkAction::SetPinned(
+                    targets.iter().map(|item| item.entry.id).collect(),
+                    !all_pinned,
+                )
+            }),
+            Message::TagInputChanged(tag) => {
+                if let Self::Loaded { tag_input, .. } = self {
+                    *tag_input = tag;
+                }
+                Task::none()
+            }
+            Message::ApplyTag => {
+                let Self::Loaded { tag_input, .. } = self else {
+                    return Task::none();
+                };
+                let tag = tag_input.trim().to_owned();
+                if tag.is_empty() {
+                    return Task::none();
+                }
+                tag_input.clear();
+                self.bulk_action(move |targets| {
+                    BulkAction::Tag(targets.iter().map(|item| item.entry.id).collect(), tag)
+                })
+            }
+            Message::Merge => self.bulk_action(|targets| {
+                BulkAction::Merge(targets.iter().map(|item| item.entry.id).collect())
+            }),
+            Message::PasteJoined => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    selection,
+                    ..
+                } = self
+                {
+                    let targets = targets(items, selection, *selected_item_cursor);
+                    if targets.is_empty() {
+                        return Task::none();
+                    }
+                    Task::done(app::Message::PasteJoined(
+                        targets.into_iter().cloned().collect(),
                     ))
                 } else {
                     Task::none()
                 }
             }
+            Message::Refreshed(new_items) => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    all_items,
+                    query,
+                    queue,
+                    selection,
+                    selection_anchor,
+                    palette,
+                    plugin_preview,
+                    code_preview,
+                    markdown_preview,
+                    image_preview,
+                    ..
+                } = self
+                {
+                    // Entries may have been edited, previews are rendered again
+                    if let Some(palette) = palette {
+                        palette.preview = None;
+                    }
+                    *plugin_preview = None;
+                    *code_preview = None;
+                    *markdown_preview = None;
+                    *image_preview = None;
+                    let exists = |id: &i32| new_items.iter().any(|item| item.entry.id == *id);
+                    queue.retain(exists);
+                    selection.retain(exists);
+                    *items = filter_items(&new_items, query);
+                    *all_items = new_items;
+                    let last = (items.len() as i32 - 1).max(0);
+                    *selected_item_cursor = (*selected_item_cursor).min(last);
+                    *selection_anchor = (*selection_anchor).min(last);
+                }
+                Task::none()
+            }
+            Message::QueryChanged(new_query) => {
+                if let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    all_items,
+                    query,
+                    selection_anchor,
+                    ..
+                } = self
+                {
+                    *items = filter_items(all_items, &new_query);
+                    *query = new_query;
+                    *selected_item_cursor = 0;
+                    *selection_anchor = 0;
+                }
+                Task::none()
+            }
+            Message::FocusSearch => text_input::focus(search_input_id()),
             Message::OpenSettings => Task::done(app::Message::OpenSettingsWindow),
+            Message::OpenTrash => Task::done(app::Message::OpenTrashWindow),
+            Message::OpenPalette(actions) => match self {
+                Self::Loaded {
+                    palette,
+                    action_menu,
+                    ..
+                } => {
+                    *action_menu = None;
+                    *palette = Some(TransformPalette {
+                        actions,
+                        ..Default::default()
+                    });
+                    text_input::focus(palette_input_id())
+                }
+                Self::Loading => Task::none(),
+            },
+            Message::PaletteFilterChanged(filter) => {
+                if let Self::Loaded {
+                    palette: Some(palette),
+                    ..
+                } = self
+                {
+                    palette.filter = filter;
+                    palette.cursor = 0;
+                }
+                Task::none()
+            }
+            Message::PaletteClicked(index) => {
+                if let Self::Loaded {
+                    palette: Some(palette),
+                    ..
+                } = self
+                {
+                    palette.cursor = index;
+                }
+                self.update(Message::ApplyTransform)
+            }
+            Message::ApplyTransform => {
+                self.render_palette_preview();
+                let Self::Loaded {
+                    modifiers, palette, ..
+                } = self
+                else {
+                    return Task::none();
+                };
+                // The preview already shows why it failed
+                let Some((_, Ok(transformed))) = palette
+                    .as_ref()
+                    .and_then(|palette| palette.preview.as_ref())
+                else {
+                    return Task::none();
+                };
+                let transformed = transformed.clone();
+                *palette = None;
+                if modifiers.shift() {
+                    Task::done(app::Message::AddEntry(transformed))
+                } else {
+                    Task::done(app::Message::PasteText(transformed))
+                }
+            }
+            Message::LinkClicked(url) => Task::done(app::Message::RunSmartAction(
+                url.to_string(),
+                SmartAction::OpenUrl,
+            )),
+            Message::PluginPreviewRendered(id, rendered) => {
+                if let Self::Loaded {
+                    plugin_preview: Some((preview_id, preview)),
+                    ..
+                } = self
+                {
+                    // The cursor may have moved on while the plugins were rendering
+                    if *preview_id == id {
+                        *preview = rendered;
+                    }
+                }
+                Task::none()
+            }
+            Message::ToggleHelp => {
+                if let Self::Loaded { show_help, .. } = self {
+                    *show_help = !*show_help;
+                }
+                Task::none()
+            }
+            Message::OpenActionMenu => {
+                let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    palette,
+                    action_menu,
+                    ..
+                } = self
+                else {
+                    return Task::none();
+                };
+                if let Some(item) = items.get(*selected_item_cursor as usize) {
+                    *palette = None;
+                    *action_menu = Some(ActionMenu {
+                        cursor: 0,
+                        actions: SmartAction::for_item(item),
+                    });
+                }
+                Task::none()
+            }
+            Message::ActionClicked(index) => {
+                if let Self::Loaded {
+                    action_menu: Some(menu),
+                    ..
+                } = self
+                {
+                    menu.cursor = index;
+                }
+                self.update(Message::RunSelectedAction)
+            }
+            Message::RunSelectedAction => {
+                let Self::Loaded {
+                    selected_item_cursor,
+                    items,
+                    action_menu,
+                    ..
+                } = self
+                else {
+                    return Task::none();
+                };
+                let (Some(item), Some(action)) = (
+                    items.get(*selected_item_cursor as usize),
+                    action_menu
+                        .take()
+                        .and_then(|menu| menu.actions.get(menu.cursor).copied()),
+                ) else {
+                    return Task::none();
+                };
+                Task::done(app::Message::RunSmartAction(
+                    item.entry.data.clone(),
+                    action,
+                ))
+            }
+            Message::RunBoundAction(bound) => {
+                let Some(item) = self.cursor_item() else {
+                    return Task::none();
+                };
+                match SmartAction::for_item(item)
+                    .into_iter()
+                    .find(|action| bound.contains(action))
+                {
+                    Some(action) => Task::done(app::Message::RunSmartAction(
+                        item.entry.data.clone(),
+                        action,
+                    )),
+                    None => Task::none(),
+                }
+            }
         }
     }
 
+    /// Builds the action from the selected entries, oldest first, and clears the selection
+    fn bulk_action(&mut self, action: impl FnOnce(Vec<&Item>) -> BulkAction) -> Task<app::Message> {
+        let Self::Loaded {
+            selected_item_cursor,
+            items,
+            selection,
+            ..
+        } = self
+        else {
+            return Task::none();
+        };
+        let targets = targets(items, selection, *selected_item_cursor);
+        if targets.is_empty() {
+            return Task::none();
+        }
+        let action = action(targets);
+        selection.clear();
+        Task::done(app::Message::BulkAction(action))
+    }
+
     pub fn view(&self) -> Element<Message> {
         fn row_bg_color(theme: &iced::Theme, row_index: usize, selected: bool) -> container::Style {
             let other_bg_color = if theme.extended_palette().is_dark {
@@ -91,33 +1043,444 @@ impl State {
             State::Loaded {
                 selected_item_cursor,
                 items,
-            } => column![
-                row![
-                    text!("Clippy"),
-                    horizontal_space(),
-                    button(text!("Settings")).on_press(Message::OpenSettings)
+                query,
+                queue,
+                selection,
+                tag_input,
+                undo_delete,
+                palette,
+                action_menu,
+                plugin_preview,
+                code_preview,
+                preview_pane,
+                markdown_preview,
+                image_preview,
+                show_help,
+                ..
+            } => {
+                let plugin_preview = plugin_preview
+                    .as_ref()
+                    .filter(|(_, preview)| !preview.is_empty())
+                    .map(|(_, preview)| {
+                        container(text(preview.as_str()).size(12))
+                            .style(container::rounded_box)
+                            .padding(6)
+                            .width(Length::Fill)
+                    });
+                let cursor_item = items.get(*selected_item_cursor as usize);
+                let pane = preview_pane.then(|| {
+                    entry_preview(
+                        cursor_item,
+                        code_preview.as_ref().map(|(_, lines)| lines.as_slice()),
+                        markdown_preview.as_ref().map(|(_, items)| items.as_slice()),
+                        image_preview
+                            .as_ref()
+                            .and_then(|(_, handle)| handle.clone()),
+                    )
+                });
+                // Shown under the list when there is no preview pane
+                let code_preview = code_preview
+                    .as_ref()
+                    .filter(|_| !preview_pane)
+                    .map(|(_, lines)| code_view(lines).max_height(CODE_PREVIEW_HEIGHT));
+                let palette = palette.as_ref().map(|palette| {
+                    let matches = palette.matches();
+                    let preview = match &palette.preview {
+                        Some((_, Ok(transformed))) => transformed.clone(),
+                        Some((_, Err(e))) => format!("Error: {e}"),
+                        None => String::new(),
+                    };
+                    column![
+                        text_input("Transform", &palette.filter)
+                            .id(palette_input_id())
+                            .on_input(Message::PaletteFilterChanged)
+                            .on_submit(Message::ApplyTransform)
+                            .size(12),
+                        Column::from_iter(matches.into_iter().enumerate().map(
+                            |(index, transform)| {
+                                let highlighted = index == palette.cursor;
+                                mouse_area(
+                                    container(text(transform.to_string()).size(12))
+                                        .style(move |theme: &iced::Theme| {
+                                            if highlighted {
+                                                container::rounded_box(theme)
+                                            } else {
+                                                container::Style::default()
+                                            }
+                                        })
+                                        .padding(4)
+                                        .width(Length::Fill),
+                                )
+                                .on_press(Message::PaletteClicked(index))
+                                .into()
+                            }
+                        )),
+                        text(preview).size(11).wrapping(text::Wrapping::None),
+                        text!("Enter: paste · Shift+Enter: save as new entry").size(11),
+                    ]
+                    .spacing(4)
+                    .padding(6)
+                });
+
+                let action_menu = action_menu.as_ref().map(|menu| {
+                    let cursor_text = cursor_item
+                        .map(|item| item.entry.data.clone())
+                        .unwrap_or_default();
+                    let actions: Element<Message> = if menu.actions.is_empty() {
+                        text!("No action for this entry").size(12).into()
+                    } else {
+                        Column::from_iter(menu.actions.iter().enumerate().map(|(index, action)| {
+                            let highlighted = index == menu.cursor;
+                            mouse_area(
+                                container(text(action.label(&cursor_text)).size(12))
+                                    .style(move |theme: &iced::Theme| {
+                                        if highlighted {
+                                            container::rounded_box(theme)
+                                        } else {
+                                            container::Style::default()
+                                        }
+                                    })
+                                    .padding(4)
+                                    .width(Length::Fill),
+                            )
+                            .on_press(Message::ActionClicked(index))
+                            .into()
+                        }))
+                        .into()
+                    };
+                    column![actions, text!("Enter: run · Esc: close").size(11)]
+                        .spacing(4)
+                        .padding(6)
+                });
+
+                let undo_toast = undo_delete.as_ref().map(|ids| {
+                    container(
+                        row![
+                            text!("{} moved to trash", ids.len()).size(12),
+                            horizontal_space(),
+                            button(text!("Undo").size(11))
+                                .padding(4)
+                                .on_press(Message::UndoDelete),
+                        ]
+                        .align_y(Alignment::Center),
+                    )
+                    .style(container::rounded_box)
+                    .padding(6)
+                });
+
+                let bulk_actions = (!selection.is_empty()).then(|| {
+                    let all_pinned = items
+                        .iter()
+                        .filter(|item| selection.contains(&item.entry.id))
+                        .all(|item| item.entry.pinned);
+                    let action_button = |label: &'static str, message: Message| {
+                        button(text(label).size(11)).padding(4).on_press(message)
+                    };
+                    column![
+                        text!("{} selected", selection.len()).size(12),
+                        row![
+                            action_button("Delete", Message::Delete),
+                            action_button(
+                                if all_pinned { "Unpin" } else { "Pin" },
+                                Message::TogglePinned
+                            ),
+                        ]
+                        .spacing(4),
+                        row![
+                            action_button("Merge", Message::Merge),
+                            action_button("Paste joined", Message::PasteJoined),
+                        ]
+                        .spacing(4),
+                        row![
+                            text_input("Tag", tag_input)
+                                .on_input(Message::TagInputChanged)
+                                .on_submit(Message::ApplyTag)
+                                .size(12),
+                            action_button("Tag", Message::ApplyTag),
+                        ]
+                        .spacing(4)
+                        .align_y(Alignment::Center),
+                    ]
+                    .spacing(4)
+                    .padding(6)
+                });
+
+                column![
+                    row![
+                        text!("Clippy"),
+                        horizontal_space(),
+                        button(text!("Trash")).on_press(Message::OpenTrash),
+                        button(text!("Settings")).on_press(Message::OpenSettings)
+                    ]
+                    .spacing(4)
+                    .align_y(Alignment::Center)
+                    .padding(10),
+                    container(
+                        text_input("Search, type:url, #tag", query)
+                            .id(search_input_id())
+                            .on_input(Message::QueryChanged)
+                            .on_submit(Message::Paste(PasteMode::Formatted))
+                            .size(12),
+                    )
+                    .padding([0, 10]),
                 ]
-                .align_y(Alignment::Center)
-                .padding(10),
-                scrollable(
-                    Column::from_iter(items.iter().enumerate().map(|(index, entry)| {
-                        container(
-                            text!("{}", entry.data)
-                                .size(13)
-                                .wrapping(text::Wrapping::None),
-                        )
-                        .style(move |theme: &iced::Theme| {
-                            row_bg_color(theme, index, index == *selected_item_cursor as usize)
+                .push_maybe(palette)
+                .push_maybe(action_menu)
+                .push({
+                    let list = scrollable(
+                        Column::from_iter(items.iter().enumerate().map(|(index, item)| {
+                            let queue_position = queue.iter().position(|id| *id == item.entry.id);
+                            let is_cursor = index == *selected_item_cursor as usize;
+                            let highlighted = is_cursor || selection.contains(&item.entry.id);
+                            let has_selection = !selection.is_empty();
+                            mouse_area(
+                                container(
+                                    row![]
+                                        .push_maybe(queue_position.map(|position| {
+                                            text!("[{}]", position + 1).size(13)
+                                        }))
+                                        .push_maybe(
+                                            item.entry.pinned.then(|| text!("★").size(13)),
+                                        )
+                                        .push_maybe(color_swatch(item))
+                                        .push(
+                                            text!("{}", item.entry.data)
+                                                .size(13)
+                                                .wrapping(text::Wrapping::None),
+                                        )
+                                        .extend(item.kinds().into_iter().map(|kind| {
+                                            let badge = match (kind, &item.entry.language) {
+                                                (ContentKind::Code, Some(language)) => {
+                                                    language.clone()
+                                                }
+                                                _ => kind.to_string(),
+                                            };
+                                            text(badge).size(11).style(text::primary).into()
+                                        }))
+                                        .extend(
+                                            item.tags
+                                                .iter()
+                                                .map(|tag| text!("#{tag}").size(11).into()),
+                                        )
+                                        .spacing(4)
+                                        .align_y(Alignment::Center),
+                                )
+                                .style(move |theme: &iced::Theme| {
+                                    let mut style = row_bg_color(theme, index, highlighted);
+                                    if is_cursor && has_selection {
+                                        style.border = border::rounded(2)
+                                            .color(theme.palette().primary)
+                                            .width(1);
+                                    }
+                                    style
+                                })
+                                .padding(8)
+                                .width(Length::Fill),
+                            )
+                            .on_press(Message::ItemClicked(index))
+                            .into()
+                        },))
+                        .spacing(4),
+                    )
+                    .height(Length::Fill);
+                    match pane {
+                        Some(pane) => Element::from(
+                            row![list.width(Length::FillPortion(1)), pane]
+                                .spacing(4)
+                                .height(Length::Fill),
+                        ),
+                        None => list.into(),
+                    }
+                })
+                .push_maybe(code_preview)
+                .push_maybe(plugin_preview)
+                .push_maybe(bulk_actions)
+                .push_maybe(undo_toast)
+                .push(
+                    container(
+                        text(if *show_help {
+                            "Enter: paste · Shift+Enter: plain text · Ctrl+Enter: type out · Space: queue · Ctrl+E: edit · Ctrl+Shift+E: $EDITOR · Ctrl+T: transform · Ctrl+F: search · Ctrl+K / Alt+key: actions · Ctrl+Space / Shift+arrows: select · Del: delete · Ctrl+Z: undo · ?: hide keys"
+                        } else {
+                            "Enter: paste · Shift+Enter: plain text · Ctrl+Enter: type out · ?: more keys"
                         })
-                        .padding(8)
-                        .width(Length::Fill)
-                        .into()
-                    },))
-                    .spacing(4),
-                ),
-            ]
-            .into(),
+                        .size(11),
+                    )
+                    .padding(6),
+                )
+                .into()
+            }
             State::Loading => center(text!("Loading...")).into(),
         }
     }
 }
+
+/// Highlighted lines with their numbers
+fn code_view(lines: &[Vec<highlight::Span>]) -> Container<'_, Message> {
+    container(
+        scrollable(Column::from_iter(lines.iter().enumerate().map(
+            |(index, spans)| {
+                let spans = spans
+                    .iter()
+                    .map(|token| {
+                        span(token.text.as_str())
+                            .color(token.color.unwrap_or(highlight::FOREGROUND))
+                    })
+                    .collect::<Vec<text::Span<'_, (), Font>>>();
+                row![
+                    text!("{}", index + 1)
+                        .size(12)
+                        .font(Font::MONOSPACE)
+                        .color(highlight::LINE_NUMBER)
+                        .align_x(alignment::Horizontal::Right)
+                        .width(32),
+                    rich_text(spans).size(12).font(Font::MONOSPACE),
+                ]
+                .spacing(8)
+                .into()
+            },
+        )))
+        .height(Length::Shrink),
+    )
+    .style(|_: &iced::Theme| container::background(highlight::BACKGROUND))
+    .padding(6)
+    .width(Length::Fill)
+}
+
+/// Entry under the cursor in full, followed by its files and metadata
+fn entry_preview<'a>(
+    item: Option<&'a Item>,
+    code: Option<&'a [Vec<highlight::Span>]>,
+    markdown_items: Option<&'a [markdown::Item]>,
+    image_handle: Option<image::Handle>,
+) -> Element<'a, Message> {
+    let Some(item) = item else {
+        return center(text!("No entry").size(12)).into();
+    };
+    let data = &item.entry.data;
+    let body: Element<Message> = match (code, markdown_items) {
+        (Some(lines), _) => code_view(lines).into(),
+        (None, Some(markdown_items)) => markdown::view(
+            markdown_items,
+            markdown::Settings::default(),
+            // The daemon runs with the default theme
+            markdown::Style::from_palette(iced::Theme::default().palette()),
+        )
+        .map(Message::LinkClicked),
+        (None, None) => text(data.as_str()).size(12).into(),
+    };
+    let files = item.formats.iter().find_map(|format| match format {
+        ClipboardContent::Files(files) => Some(files),
+        _ => None,
+    });
+
+    column![
+        text!(
+            "{} lines · {} chars",
+            data.lines().count(),
+            data.chars().count()
+        )
+        .size(11),
+        scrollable(body).height(Length::Fill),
+    ]
+    .push_maybe(image_handle.map(|handle| {
+        image(handle)
+            .content_fit(ContentFit::Contain)
+            .width(Length::Fill)
+            .height(IMAGE_PREVIEW_HEIGHT)
+    }))
+    .push_maybe(files.map(|files| {
+        Column::from_iter(files.iter().map(|file| text(file.as_str()).size(11).into()))
+    }))
+    .push(horizontal_rule(1))
+    .push(text!("Added {}", item.entry.added_at.format("%Y-%m-%d %H:%M")).size(11))
+    .push_maybe(
+        item.entry
+            .source_app
+            .as_ref()
+            .map(|source_app| text!("Copied from {source_app}").size(11)),
+    )
+    .push(text!("Pasted {} times", item.entry.use_count).size(11))
+    .spacing(6)
+    .padding(6)
+    .width(Length::FillPortion(2))
+    .into()
+}
+
+/// First image file among the files of the entry, or the path it holds
+fn image_path(item: &Item) -> Option<PathBuf> {
+    let files = item.formats.iter().find_map(|format| match format {
+        ClipboardContent::Files(files) => Some(files),
+        _ => None,
+    });
+    files
+        .into_iter()
+        .flatten()
+        .map(|file| file.strip_prefix("file://").unwrap_or(file))
+        .chain(
+            item.kinds()
+                .contains(&ContentKind::Path)
+                .then_some(item.entry.data.trim()),
+        )
+        .map(Path::new)
+        .find(|path| {
+            path.extension()
+                .and_then(OsStr::to_str)
+                .is_some_and(|extension| {
+                    IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                })
+                && path.is_file()
+        })
+        .map(Path::to_path_buf)
+}
+
+/// Square filled with the color the entry holds, if any
+fn color_swatch<'a>(item: &Item) -> Option<Element<'a, Message>> {
+    if !item.kinds().contains(&ContentKind::Color) {
+        return None;
+    }
+    let (color, _) = color::parse(&item.entry.data)?;
+    Some(
+        container(Space::new(14, 14))
+            .style(move |theme: &iced::Theme| {
+                let mut style = container::background(color);
+                style.border = border::rounded(2).color(theme.palette().text).width(1);
+                style
+            })
+            .into(),
+    )
+}
+
+/// Selected entries, or the one under the cursor when nothing is selected, oldest first
+fn targets<'a>(items: &'a [Item], selection: &HashSet<i32>, cursor: i32) -> Vec<&'a Item> {
+    let mut targets = if selection.is_empty() {
+        items.get(cursor as usize).into_iter().collect::<Vec<_>>()
+    } else {
+        items
+            .iter()
+            .filter(|item| selection.contains(&item.entry.id))
+            .collect()
+    };
+    targets.sort_by_key(|item| item.entry.added_at);
+    targets
+}
+
+fn select_range(items: &[Item], selection: &mut HashSet<i32>, anchor: i32, cursor: i32) {
+    let (start, end) = (
+        anchor.min(cursor).max(0) as usize,
+        anchor.max(cursor) as usize,
+    );
+    *selection = items
+        .iter()
+        .skip(start)
+        .take(end + 1 - start)
+        .map(|item| item.entry.id)
+        .collect();
+}
+
+fn toggle_selected(items: &[Item], selection: &mut HashSet<i32>, index: usize) {
+    if let Some(item) = items.get(index) {
+        if !selection.remove(&item.entry.id) {
+            selection.insert(item.entry.id);
+        }
+    }
+}
@@ -1,8 +1,16 @@
+pub mod editor;
+pub mod error;
 pub mod history;
 pub mod settings;
+pub mod trash;
+pub mod yank_pop;
 
 #[derive(Debug)]
 pub enum Window {
+    Editor(editor::State),
+    Error(error::State),
     History(history::State),
     Settings(settings::State),
+    Trash(trash::State),
+    YankPop(yank_pop::State),
 }
@@ -1,14 +1,41 @@
 use iced::{
     border,
-    widget::{button, column, container, horizontal_rule, row, text},
-    Alignment, Element, Task,
+    widget::{
+        button, checkbox, column, container, horizontal_rule, pick_list, row, scrollable, text,
+        text_input, Column,
+    },
+    Alignment, Element, Length, Task,
 };
 
-use crate::app::{self, Shortcut};
+use crate::{
+    app::{self, Shortcut},
+    config::{Config, RepasteBehavior},
+    hooks::{CaptureHook, FormatFilter, HookPattern, KindFilter},
+    hotkey::GlobalShortcut,
+    input::{InputBackendKind, KeyboardLayout},
+    paste::{chord_string, PasteAction, PasteRule, PasteSuffix, YankPopReplace},
+    plugin::{self, Capability, PluginStatus},
+    scripting::{self, ScriptStatus},
+    smart_action::{ActionKey, SmartAction},
+    transform::{Pipeline, ReplacePattern, Step, Transform},
+    utils::shortcut_to_chord,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutField {
+    Toggle,
+    PasteChord,
+    QuickPasteModifiers,
+    YankPop,
+    QueuePaste,
+    Collect,
+    Pipeline(usize),
+}
 
 #[derive(Debug)]
 pub enum ShortcutSelectionState {
-    Listening(Shortcut),
+    /// Holds the last shortcut pressed since listening started, if any
+    Listening(ShortcutField, Option<Shortcut>),
     NotListening,
 }
 
@@ -16,79 +43,938 @@ pub enum ShortcutSelectionState {
 pub struct State {
     pub toggle_shortcut: Shortcut,
     pub shortcut_selection_state: ShortcutSelectionState,
+    pub config: Config,
+    /// Invalid patterns typed for each capture hook, kept out of the config
+    pub invalid_hook_patterns: Vec<Option<HookPattern>>,
+    /// Text pipelines are previewed against, the entry selected in the history at opening
+    pub preview_text: String,
+    /// Output of each pipeline on the preview text, recomputed when either changes
+    pub pipeline_previews: Vec<String>,
+    pub scripts: Vec<ScriptStatus>,
+    pub plugins: Vec<PluginStatus>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     NewShortcutInput(Shortcut),
-    ToggleShortcutSelection,
+    ToggleShortcutSelection(ShortcutField),
+    InputBackendSelected(InputBackendKind),
+    AddPasteRule,
+    RemovePasteRule(usize),
+    PasteRuleClassChanged(usize, String),
+    PasteRuleTitleChanged(usize, String),
+    PasteRuleActionSelected(usize, PasteAction),
+    KeyboardLayoutSelected(KeyboardLayout),
+    TypeOutDelayChanged(String),
+    RestoreClipboardToggled(bool),
+    PreviewPaneToggled(bool),
+    RestoreClipboardDelayChanged(String),
+    RepasteBehaviorSelected(RepasteBehavior),
+    QuickPasteToggled(bool),
+    ClearYankPopShortcut,
+    YankPopReplaceSelected(YankPopReplace),
+    ClearQueuePasteShortcut,
+    QueuePasteSuffixSelected(PasteSuffix),
+    ClearCollectShortcut,
+    CollectSeparatorChanged(String),
+    JoinSeparatorChanged(String),
+    TrashRetentionChanged(String),
+    AddPipeline,
+    RemovePipeline(usize),
+    PipelineNameChanged(usize, String),
+    ClearPipelineShortcut(usize),
+    AddPipelineStep(usize),
+    RemovePipelineStep(usize, usize),
+    PipelineStepChanged(usize, usize, Step),
+    PreviewTextChanged(String),
+    ReloadScripts,
+    ScriptsReloaded(Vec<ScriptStatus>),
+    PluginToggled(String, bool),
+    CapabilityToggled(String, Capability, bool),
+    ReloadPlugins,
+    PluginsReloaded(Vec<PluginStatus>),
+    AddCaptureHook,
+    RemoveCaptureHook(usize),
+    CaptureHookCommandChanged(usize, String),
+    CaptureHookPatternChanged(usize, String),
+    CaptureHookFormatSelected(usize, FormatFilter),
+    CaptureHookKindSelected(usize, KindFilter),
+    CaptureHookReplaceToggled(usize, bool),
+    CaptureHookTimeoutChanged(usize, String),
+    SmartActionKeyChanged(SmartAction, String),
 }
 
 impl State {
-    pub fn new(toggle_shortcut: Shortcut) -> State {
-        State {
+    pub fn new(
+        toggle_shortcut: Shortcut,
+        config: Config,
+        preview_text: String,
+        scripts: Vec<ScriptStatus>,
+        plugins: Vec<PluginStatus>,
+    ) -> State {
+        let mut state = State {
             toggle_shortcut,
             shortcut_selection_state: ShortcutSelectionState::NotListening,
-        }
+            invalid_hook_patterns: vec![None; config.capture_hooks.len()],
+            config,
+            preview_text,
+            pipeline_previews: Vec::new(),
+            scripts,
+            plugins,
+        };
+        state.render_pipeline_previews();
+        state
     }
 
     pub fn update(&mut self, message: Message) -> Task<app::Message> {
         match message {
             Message::NewShortcutInput(new_shortcut) => match self.shortcut_selection_state {
-                ShortcutSelectionState::Listening(ref mut current_shortcut) => {
-                    *current_shortcut = new_shortcut;
+                ShortcutSelectionState::Listening(_, ref mut current_shortcut) => {
+                    *current_shortcut = Some(new_shortcut);
                     Task::none()
                 }
                 ShortcutSelectionState::NotListening => Task::none(),
             },
-            Message::ToggleShortcutSelection => {
+            Message::ToggleShortcutSelection(field) => {
                 let (new_state, task) = match self.shortcut_selection_state {
-                    ShortcutSelectionState::Listening(ref shortcut) => {
-                        self.toggle_shortcut = shortcut.clone();
+                    ShortcutSelectionState::Listening(field, Some(ref shortcut)) => {
+                        let shortcut = shortcut.clone();
                         (
                             ShortcutSelectionState::NotListening,
-                            Task::done(app::Message::UpdateToggleShortcut(shortcut.clone())),
+                            self.apply_shortcut(field, shortcut),
                         )
                     }
-                    ShortcutSelectionState::NotListening => (
-                        ShortcutSelectionState::Listening(self.toggle_shortcut.clone()),
-                        Task::none(),
-                    ),
+                    ShortcutSelectionState::Listening(_, None) => {
+                        (ShortcutSelectionState::NotListening, Task::none())
+                    }
+                    ShortcutSelectionState::NotListening => {
+                        (ShortcutSelectionState::Listening(field, None), Task::none())
+                    }
                 };
                 self.shortcut_selection_state = new_state;
                 task
             }
+            Message::InputBackendSelected(input_backend) => {
+                self.config.input_backend = input_backend;
+                self.config_changed()
+            }
+            Message::AddPasteRule => {
+                self.config.paste_rules.push(PasteRule::default());
+                self.config_changed()
+            }
+            Message::RemovePasteRule(index) => {
+                if index >= self.config.paste_rules.len() {
+                    return Task::none();
+                }
+                self.config.paste_rules.remove(index);
+                self.config_changed()
+            }
+            Message::PasteRuleClassChanged(index, window_class) => {
+                let Some(rule) = self.config.paste_rules.get_mut(index) else {
+                    return Task::none();
+                };
+                rule.window_class = window_class;
+                self.config_changed()
+            }
+            Message::PasteRuleTitleChanged(index, window_title) => {
+                let Some(rule) = self.config.paste_rules.get_mut(index) else {
+                    return Task::none();
+                };
+                rule.window_title = window_title;
+                self.config_changed()
+            }
+            Message::PasteRuleActionSelected(index, action) => {
+                let Some(rule) = self.config.paste_rules.get_mut(index) else {
+                    return Task::none();
+                };
+                rule.action = action;
+                self.config_changed()
+            }
+            Message::KeyboardLayoutSelected(keyboard_layout) => {
+                self.config.keyboard_layout = keyboard_layout;
+                self.config_changed()
+            }
+            Message::TypeOutDelayChanged(delay) => match parse_number(&delay) {
+                Some(delay) => {
+                    self.config.type_out_delay_ms = delay;
+                    self.config_changed()
+                }
+                None => Task::none(),
+            },
+            Message::RestoreClipboardToggled(restore_clipboard) => {
+                self.config.restore_clipboard = restore_clipboard;
+                self.config_changed()
+            }
+            Message::PreviewPaneToggled(preview_pane) => {
+                self.config.preview_pane = preview_pane;
+                self.config_changed()
+            }
+            Message::RestoreClipboardDelayChanged(delay) => match parse_number(&delay) {
+                Some(delay) => {
+                    self.config.restore_clipboard_delay_ms = delay;
+                    self.config_changed()
+                }
+                None => Task::none(),
+            },
+            Message::RepasteBehaviorSelected(repaste_behavior) => {
+                self.config.repaste_behavior = repaste_behavior;
+                self.config_changed()
+            }
+            Message::QuickPasteToggled(quick_paste_enabled) => {
+                self.config.quick_paste_enabled = quick_paste_enabled;
+                self.config_changed()
+            }
+            Message::ClearYankPopShortcut => {
+                self.config.yank_pop_shortcut = None;
+                self.config_changed()
+            }
+            Message::YankPopReplaceSelected(yank_pop_replace) => {
+                self.config.yank_pop_replace = yank_pop_replace;
+                self.config_changed()
+            }
+            Message::ClearQueuePasteShortcut => {
+                self.config.queue_paste_shortcut = None;
+                self.config_changed()
+            }
+            Message::QueuePasteSuffixSelected(queue_paste_suffix) => {
+                self.config.queue_paste_suffix = queue_paste_suffix;
+                self.config_changed()
+            }
+            Message::ClearCollectShortcut => {
+                self.config.collect_shortcut = None;
+                self.config_changed()
+            }
+            Message::CollectSeparatorChanged(separator) => {
+                self.config.collect_separator = unescape_separator(&separator);
+                self.config_changed()
+            }
+            Message::JoinSeparatorChanged(separator) => {
+                self.config.join_separator = unescape_separator(&separator);
+                self.config_changed()
+            }
+            Message::TrashRetentionChanged(days) => match parse_number(&days) {
+                Some(days) => {
+                    self.config.trash_retention_days = days;
+                    self.config_changed()
+                }
+                None => Task::none(),
+            },
+            Message::AddPipeline => {
+                self.config.pipelines.push(Pipeline::default());
+                self.render_pipeline_previews();
+                self.config_changed()
+            }
+            Message::RemovePipeline(index) => {
+                if index >= self.config.pipelines.len() {
+                    return Task::none();
+                }
+                self.config.pipelines.remove(index);
+                self.render_pipeline_previews();
+                self.config_changed()
+            }
+            Message::PipelineNameChanged(index, name) => {
+                let Some(pipeline) = self.config.pipelines.get_mut(index) else {
+                    return Task::none();
+                };
+                pipeline.name = name;
+                self.config_changed()
+            }
+            Message::ClearPipelineShortcut(index) => {
+                let Some(pipeline) = self.config.pipelines.get_mut(index) else {
+                    return Task::none();
+                };
+                pipeline.shortcut = None;
+                self.config_changed()
+            }
+            Message::AddPipelineStep(index) => {
+                let Some(pipeline) = self.config.pipelines.get_mut(index) else {
+                    return Task::none();
+                };
+                pipeline.steps.push(Step::Builtin(Transform::Trim));
+                self.render_pipeline_previews();
+                self.config_changed()
+            }
+            Message::RemovePipelineStep(index, step_index) => {
+                let Some(pipeline) = self.config.pipelines.get_mut(index) else {
+                    return Task::none();
+                };
+                if step_index >= pipeline.steps.len() {
+                    return Task::none();
+                }
+                pipeline.steps.remove(step_index);
+                self.render_pipeline_previews();
+                self.config_changed()
+            }
+            Message::PipelineStepChanged(index, step_index, step) => {
+                let Some(current) = self
+                    .config
+                    .pipelines
+                    .get_mut(index)
+                    .and_then(|pipeline| pipeline.steps.get_mut(step_index))
+                else {
+                    return Task::none();
+                };
+                *current = step;
+                self.render_pipeline_previews();
+                self.config_changed()
+            }
+            Message::PreviewTextChanged(preview_text) => {
+                self.preview_text = preview_text;
+                self.render_pipeline_previews();
+                Task::none()
+            }
+            Message::ReloadScripts => Task::done(app::Message::ReloadScripts),
+            Message::ScriptsReloaded(scripts) => {
+                self.scripts = scripts;
+                Task::none()
+            }
+            Message::PluginToggled(file, enabled) => {
+                self.config.plugins.entry(file).or_default().enabled = enabled;
+                self.config_changed()
+            }
+            Message::CapabilityToggled(file, capability, granted) => {
+                let settings = self.config.plugins.entry(file).or_default();
+                settings.granted.retain(|granted| *granted != capability);
+                if granted {
+                    settings.granted.push(capability);
+                }
+                self.config_changed()
+            }
+            Message::ReloadPlugins => Task::done(app::Message::ReloadPlugins),
+            Message::PluginsReloaded(plugins) => {
+                self.plugins = plugins;
+                Task::none()
+            }
+            Message::AddCaptureHook => {
+                self.config.capture_hooks.push(CaptureHook::default());
+                self.invalid_hook_patterns.push(None);
+                self.config_changed()
+            }
+            Message::RemoveCaptureHook(index) => {
+                if index >= self.config.capture_hooks.len() {
+                    return Task::none();
+                }
+                self.config.capture_hooks.remove(index);
+                self.invalid_hook_patterns.remove(index);
+                self.config_changed()
+            }
+            Message::CaptureHookCommandChanged(index, command) => {
+                let Some(hook) = self.config.capture_hooks.get_mut(index) else {
+                    return Task::none();
+                };
+                hook.command = command;
+                self.config_changed()
+            }
+            Message::CaptureHookPatternChanged(index, pattern) => {
+                let (Some(hook), Some(invalid_pattern)) = (
+                    self.config.capture_hooks.get_mut(index),
+                    self.invalid_hook_patterns.get_mut(index),
+                ) else {
+                    return Task::none();
+                };
+                let pattern = HookPattern::new(pattern);
+                if pattern.error().is_some() {
+                    *invalid_pattern = Some(pattern);
+                    return Task::none();
+                }
+                *invalid_pattern = None;
+                hook.pattern = pattern;
+                self.config_changed()
+            }
+            Message::CaptureHookFormatSelected(index, format) => {
+                let Some(hook) = self.config.capture_hooks.get_mut(index) else {
+                    return Task::none();
+                };
+                hook.format = format;
+                self.config_changed()
+            }
+            Message::CaptureHookKindSelected(index, kind) => {
+                let Some(hook) = self.config.capture_hooks.get_mut(index) else {
+                    return Task::none();
+                };
+                hook.kind = kind;
+                self.config_changed()
+            }
+            Message::CaptureHookReplaceToggled(index, replace_entry) => {
+                let Some(hook) = self.config.capture_hooks.get_mut(index) else {
+                    return Task::none();
+                };
+                hook.replace_entry = replace_entry;
+                self.config_changed()
+            }
+            Message::CaptureHookTimeoutChanged(index, timeout) => {
+                match (
+                    self.config.capture_hooks.get_mut(index),
+                    parse_number(&timeout),
+                ) {
+                    (Some(hook), Some(timeout)) => {
+                        hook.timeout_ms = timeout;
+                        self.config_changed()
+                    }
+                    _ => Task::none(),
+                }
+            }
+            Message::SmartActionKeyChanged(action, key) => {
+                let keys = &mut self.config.smart_action_keys;
+                keys.retain(|binding| binding.action != action);
+                // The last typed character replaces the previous key
+                if let Some(key) = key.chars().last() {
+                    keys.push(ActionKey {
+                        action,
+                        key: key.to_ascii_lowercase(),
+                    });
+                }
+                self.config_changed()
+            }
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
-        let (key_string, is_toggle_shortcut_edition_enabled) = match self.shortcut_selection_state {
-            ShortcutSelectionState::Listening(ref shortcut) => (shortcut_string(shortcut), true),
-            ShortcutSelectionState::NotListening => (shortcut_string(&self.toggle_shortcut), false),
-        };
+    fn apply_shortcut(&mut self, field: ShortcutField, shortcut: Shortcut) -> Task<app::Message> {
+        match field {
+            ShortcutField::Toggle => {
+                self.toggle_shortcut = shortcut.clone();
+                Task::done(app::Message::UpdateToggleShortcut(shortcut))
+            }
+            ShortcutField::PasteChord => {
+                self.config.paste_chord = shortcut_to_chord(&shortcut);
+                self.config_changed()
+            }
+            ShortcutField::QuickPasteModifiers => {
+                self.config.quick_paste_modifiers = shortcut.modifiers.into();
+                self.config_changed()
+            }
+            ShortcutField::YankPop => {
+                self.config.yank_pop_shortcut = Some((&shortcut).into());
+                self.config_changed()
+            }
+            ShortcutField::QueuePaste => {
+                self.config.queue_paste_shortcut = Some((&shortcut).into());
+                self.config_changed()
+            }
+            ShortcutField::Collect => {
+                self.config.collect_shortcut = Some((&shortcut).into());
+                self.config_changed()
+            }
+            ShortcutField::Pipeline(index) => match self.config.pipelines.get_mut(index) {
+                Some(pipeline) => {
+                    pipeline.shortcut = Some((&shortcut).into());
+                    self.config_changed()
+                }
+                None => Task::none(),
+            },
+        }
+    }
 
-        let toggle_shortcut_button_label = text!("Toggle shortcut: ");
+    fn render_pipeline_previews(&mut self) {
+        self.pipeline_previews = self
+            .config
+            .pipelines
+            .iter()
+            .map(|pipeline| match pipeline.apply(&self.preview_text) {
+                Ok(transformed) => transformed,
+                Err(e) => format!("Error: {e}"),
+            })
+            .collect();
+    }
 
-        let toggle_shortcut_button = button(text(key_string))
+    fn config_changed(&self) -> Task<app::Message> {
+        Task::done(app::Message::UpdateConfig(self.config.clone()))
+    }
+
+    fn shortcut_button(&self, field: ShortcutField) -> Element<Message> {
+        let (key_string, is_edition_enabled) = match self.shortcut_selection_state {
+            ShortcutSelectionState::Listening(listening_field, Some(ref shortcut))
+                if listening_field == field =>
+            {
+                (shortcut_string(shortcut), true)
+            }
+            ShortcutSelectionState::Listening(listening_field, None)
+                if listening_field == field =>
+            {
+                (self.field_string(field), true)
+            }
+            _ => (self.field_string(field), false),
+        };
+
+        button(text(key_string))
             .style(move |theme, status| {
                 let mut style = button::primary(theme, status);
-                if is_toggle_shortcut_edition_enabled {
+                if is_edition_enabled {
                     style.border = border::rounded(2).color(theme.palette().danger).width(3);
                 }
                 style
             })
-            .on_press(Message::ToggleShortcutSelection);
+            .on_press(Message::ToggleShortcutSelection(field))
+            .into()
+    }
 
-        column![
-            text!("Settings").size(30),
-            container(horizontal_rule(2)).padding([10, 0]),
-            row![toggle_shortcut_button_label, toggle_shortcut_button].align_y(Alignment::Center)
-        ]
-        .padding(16)
+    fn field_string(&self, field: ShortcutField) -> String {
+        match field {
+            ShortcutField::Toggle => shortcut_string(&self.toggle_shortcut),
+            ShortcutField::PasteChord => chord_string(&self.config.paste_chord),
+            ShortcutField::QuickPasteModifiers => {
+                format!("{} + 1..9", self.config.quick_paste_modifiers)
+            }
+            ShortcutField::YankPop => optional_shortcut_string(self.config.yank_pop_shortcut),
+            ShortcutField::QueuePaste => optional_shortcut_string(self.config.queue_paste_shortcut),
+            ShortcutField::Collect => optional_shortcut_string(self.config.collect_shortcut),
+            ShortcutField::Pipeline(index) => optional_shortcut_string(
+                self.config
+                    .pipelines
+                    .get(index)
+                    .and_then(|pipeline| pipeline.shortcut),
+            ),
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let paste_rules = Column::from_iter(self.config.paste_rules.iter().enumerate().map(
+            |(index, rule)| {
+                row![
+                    text_input("Window class", &rule.window_class)
+                        .on_input(move |class| Message::PasteRuleClassChanged(index, class)),
+                    text_input("Window title", &rule.window_title)
+                        .on_input(move |title| Message::PasteRuleTitleChanged(index, title)),
+                    pick_list(
+                        PasteAction::presets(),
+                        Some(rule.action.clone()),
+                        move |action| { Message::PasteRuleActionSelected(index, action) }
+                    ),
+                    button(text!("X")).on_press(Message::RemovePasteRule(index)),
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center)
+                .into()
+            },
+        ))
+        .spacing(4);
+
+        let capture_hooks = Column::from_iter(self.config.capture_hooks.iter().enumerate().map(
+            |(index, hook)| {
+                let pattern = self
+                    .invalid_hook_patterns
+                    .get(index)
+                    .and_then(Option::as_ref)
+                    .unwrap_or(&hook.pattern);
+                column![
+                    row![
+                        text_input("Command", &hook.command).on_input(move |command| {
+                            Message::CaptureHookCommandChanged(index, command)
+                        }),
+                        button(text!("X")).on_press(Message::RemoveCaptureHook(index)),
+                    ]
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+                    row![
+                        text_input("Regex filter", pattern.as_str()).on_input(move |pattern| {
+                            Message::CaptureHookPatternChanged(index, pattern)
+                        }),
+                        pick_list(FormatFilter::ALL, Some(hook.format), move |format| {
+                            Message::CaptureHookFormatSelected(index, format)
+                        }),
+                        pick_list(KindFilter::ALL, Some(hook.kind), move |kind| {
+                            Message::CaptureHookKindSelected(index, kind)
+                        }),
+                        checkbox("Replace entry", hook.replace_entry).on_toggle(
+                            move |replace_entry| {
+                                Message::CaptureHookReplaceToggled(index, replace_entry)
+                            }
+                        ),
+                        text!("timeout (ms)"),
+                        text_input("0", &hook.timeout_ms.to_string())
+                            .on_input(move |timeout| {
+                                Message::CaptureHookTimeoutChanged(index, timeout)
+                            })
+                            .width(80),
+                    ]
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+                ]
+                .push_maybe(
+                    pattern
+                        .error()
+                        .map(|error| text(error.to_string()).style(text::danger).size(12)),
+                )
+                .spacing(4)
+                .into()
+            },
+        ))
+        .spacing(8);
+
+        let smart_action_keys = Column::from_iter(SmartAction::ALL.into_iter().map(|action| {
+            let key = self
+                .config
+                .smart_action_keys
+                .iter()
+                .find(|binding| binding.action == action)
+                .map(|binding| binding.key.to_string())
+                .unwrap_or_default();
+            row![
+                text!("{action}").width(200),
+                text!("Alt + "),
+                text_input("None", &key)
+                    .on_input(move |key| Message::SmartActionKeyChanged(action, key))
+                    .width(60),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center)
+            .into()
+        }))
+        .spacing(4);
+
+        let pipelines = Column::from_iter(self.config.pipelines.iter().enumerate().map(
+            |(index, pipeline)| {
+                let preview = self
+                    .pipeline_previews
+                    .get(index)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                column![
+                    row![
+                        text_input("Name", &pipeline.name)
+                            .on_input(move |name| Message::PipelineNameChanged(index, name)),
+                        self.shortcut_button(ShortcutField::Pipeline(index)),
+                        button(text!("Clear")).on_press(Message::ClearPipelineShortcut(index)),
+                        button(text!("X")).on_press(Message::RemovePipeline(index)),
+                    ]
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+                    Column::from_iter(
+                        pipeline
+                            .steps
+                            .iter()
+                            .enumerate()
+                            .map(|(step_index, step)| step_row(index, step_index, step)),
+                    )
+                    .spacing(4),
+                    button(text!("Add step")).on_press(Message::AddPipelineStep(index)),
+                    container(text(preview).size(12))
+                        .style(container::rounded_box)
+                        .padding(6)
+                        .width(Length::Fill),
+                ]
+                .spacing(4)
+                .padding([0, 16])
+                .into()
+            },
+        ))
+        .spacing(12);
+
+        let scripts = Column::from_iter(self.scripts.iter().map(|script| {
+            match &script.error {
+                Some(error) => text!("{}: {error}", script.name)
+                    .style(text::danger)
+                    .size(12),
+                None => text!("{}: loaded", script.name).size(12),
+            }
+            .into()
+        }))
+        .spacing(4)
+        .padding([0, 16]);
+        let plugins = Column::from_iter(self.plugins.iter().map(|status| {
+            let settings = self
+                .config
+                .plugins
+                .get(&status.file)
+                .cloned()
+                .unwrap_or_default();
+            let label = match &status.manifest {
+                Some(manifest) => {
+                    format!("{} {} ({})", manifest.name, manifest.version, status.file)
+                }
+                None => status.file.clone(),
+            };
+            let file = status.file.clone();
+            column![checkbox(label, settings.enabled)
+                .on_toggle(move |enabled| Message::PluginToggled(file.clone(), enabled))]
+            .push_maybe(status.manifest.as_ref().map(|manifest| {
+                let mut provides = Vec::new();
+                if !manifest.transforms.is_empty() {
+                    provides.push(format!(
+                        "transformations: {}",
+                        manifest.transforms.join(", ")
+                    ));
+                }
+                if manifest.classifier {
+                    provides.push("classifier".into());
+                }
+                if manifest.preview {
+                    provides.push("preview".into());
+                }
+                text!("Provides {}", provides.join(" · ")).size(12)
+            }))
+            .extend(
+                status
+                    .manifest
+                    .iter()
+                    .flat_map(|manifest| manifest.capabilities.iter().copied())
+                    .map(|capability| {
+                        let file = status.file.clone();
+                        checkbox(
+                            format!("Allow: {capability}"),
+                            settings.granted.contains(&capability),
+                        )
+                        .on_toggle(move |granted| {
+                            Message::CapabilityToggled(file.clone(), capability, granted)
+                        })
+                        .size(14)
+                        .into()
+                    }),
+            )
+            .push_maybe(
+                status
+                    .error
+                    .as_ref()
+                    .map(|error| text(error.as_str()).style(text::danger).size(12)),
+            )
+            .spacing(4)
+            .into()
+        }))
+        .spacing(8)
+        .padding([0, 16]);
+        let plugins_dir = plugin::plugins_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let scripts_dir = scripting::scripts_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+
+        scrollable(
+            column![
+                text!("Settings").size(30),
+                container(horizontal_rule(2)).padding([10, 0]),
+                row![
+                    text!("Toggle shortcut: "),
+                    self.shortcut_button(ShortcutField::Toggle)
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text!("Input backend: "),
+                    pick_list(
+                        InputBackendKind::ALL,
+                        Some(self.config.input_backend),
+                        Message::InputBackendSelected
+                    )
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text!("Paste keys: "),
+                    self.shortcut_button(ShortcutField::PasteChord)
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text!("Type out layout: "),
+                    pick_list(
+                        KeyboardLayout::ALL,
+                        Some(self.config.keyboard_layout),
+                        Message::KeyboardLayoutSelected
+                    )
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text!("Type out delay (ms): "),
+                    text_input("0", &self.config.type_out_delay_ms.to_string())
+                        .on_input(Message::TypeOutDelayChanged)
+                        .width(80)
+                ]
+                .align_y(Alignment::Center),
+                checkbox(
+                    "Preview the selected entry next to the history",
+                    self.config.preview_pane
+                )
+                .on_toggle(Message::PreviewPaneToggled),
+                row![
+                    checkbox(
+                        "Restore previous clipboard after pasting, delay (ms): ",
+                        self.config.restore_clipboard
+                    )
+                    .on_toggle(Message::RestoreClipboardToggled),
+                    text_input("0", &self.config.restore_clipboard_delay_ms.to_string())
+                        .on_input(Message::RestoreClipboardDelayChanged)
+                        .width(80)
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text!("Pasted entry: "),
+                    pick_list(
+                        RepasteBehavior::ALL,
+                        Some(self.config.repaste_behavior),
+                        Message::RepasteBehaviorSelected
+                    )
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    checkbox("Quick paste Nth entry: ", self.config.quick_paste_enabled)
+                        .on_toggle(Message::QuickPasteToggled),
+                    self.shortcut_button(ShortcutField::QuickPasteModifiers)
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text!("Yank pop: "),
+                    self.shortcut_button(ShortcutField::YankPop),
+                    button(text!("Clear")).on_press(Message::ClearYankPopShortcut),
+                    pick_list(
+                        YankPopReplace::ALL,
+                        Some(self.config.yank_pop_replace),
+                        Message::YankPopReplaceSelected
+                    )
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center),
+                row![
+                    text!("Paste next queued: "),
+                    self.shortcut_button(ShortcutField::QueuePaste),
+                    button(text!("Clear")).on_press(Message::ClearQueuePasteShortcut),
+                    text!(" then "),
+                    pick_list(
+                        PasteSuffix::ALL,
+                        Some(self.config.queue_paste_suffix),
+                        Message::QueuePasteSuffixSelected
+                    )
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center),
+                row![
+                    text!("Collect mode: "),
+                    self.shortcut_button(ShortcutField::Collect),
+                    button(text!("Clear")).on_press(Message::ClearCollectShortcut),
+                    text!(" separator "),
+                    text_input("\\n", &escape_separator(&self.config.collect_separator))
+                        .on_input(Message::CollectSeparatorChanged)
+                        .width(80)
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center),
+                row![
+                    text!("Merge and paste joined separator: "),
+                    text_input("\\n", &escape_separator(&self.config.join_separator))
+                        .on_input(Message::JoinSeparatorChanged)
+                        .width(80)
+                ]
+                .align_y(Alignment::Center),
+                row![
+                    text!("Purge trash after (days, 0 = never): "),
+                    text_input("0", &self.config.trash_retention_days.to_string())
+                        .on_input(Message::TrashRetentionChanged)
+                        .width(80)
+                ]
+                .align_y(Alignment::Center),
+                text!("Paste rules (first match wins)"),
+                paste_rules,
+                button(text!("Add rule")).on_press(Message::AddPasteRule),
+                text!("Capture hooks, run with the entry on stdin and JOY_CLIPPY_* env vars"),
+                capture_hooks,
+                button(text!("Add hook")).on_press(Message::AddCaptureHook),
+                text!("Smart action keys in the history window (Ctrl+K lists all actions)"),
+                smart_action_keys,
+                text!("Transformation pipelines"),
+                row![
+                    text!("Preview text: "),
+                    text_input("Text to preview pipelines on", &self.preview_text)
+                        .on_input(Message::PreviewTextChanged)
+                ]
+                .align_y(Alignment::Center),
+                pipelines,
+                button(text!("Add pipeline")).on_press(Message::AddPipeline),
+                row![
+                    text!("Scripts in {scripts_dir}"),
+                    button(text!("Reload")).on_press(Message::ReloadScripts)
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center),
+                scripts,
+                row![
+                    text!("Plugins in {plugins_dir}"),
+                    button(text!("Reload")).on_press(Message::ReloadPlugins)
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center),
+                plugins,
+            ]
+            .spacing(8)
+            .padding(16)
+            .width(Length::Fill),
+        )
         .into()
     }
 }
 
+fn step_row(index: usize, step_index: usize, step: &Step) -> Element<Message> {
+    let changed = move |step| Message::PipelineStepChanged(index, step_index, step);
+    let argument = move |value: &str, step: fn(String) -> Step| {
+        text_input("", &escape_separator(value))
+            .on_input(move |value| changed(step(unescape_separator(&value))))
+            .width(100)
+    };
+    let arguments: Element<Message> = match step {
+        Step::Builtin(_) => row![].into(),
+        Step::Replace {
+            pattern,
+            replacement,
+        } => row![
+            text_input("Pattern", pattern.as_str()).on_input({
+                let replacement = replacement.clone();
+                move |pattern| {
+                    changed(Step::Replace {
+                        pattern: ReplacePattern::new(pattern),
+                        replacement: replacement.clone(),
+                    })
+                }
+            }),
+            text_input("Replacement", replacement).on_input({
+                let pattern = pattern.clone();
+                move |replacement| {
+                    changed(Step::Replace {
+                        pattern: pattern.clone(),
+                        replacement,
+                    })
+                }
+            }),
+        ]
+        .spacing(4)
+        .into(),
+        Step::PrefixLines(prefix) => argument(prefix, Step::PrefixLines).into(),
+        Step::SuffixLines(suffix) => argument(suffix, Step::SuffixLines).into(),
+        Step::Wrap(wrapper) => argument(wrapper, Step::Wrap).into(),
+        Step::JoinLines(separator) => argument(separator, Step::JoinLines).into(),
+    };
+    let error = match step {
+        Step::Replace { pattern, .. } => pattern.error(),
+        _ => None,
+    };
+    column![row![
+        pick_list(Step::presets(), Some(step.clone()), changed),
+        arguments,
+        button(text!("X")).on_press(Message::RemovePipelineStep(index, step_index)),
+    ]
+    .spacing(4)
+    .align_y(Alignment::Center)]
+    .push_maybe(error.map(|error| text(error.to_string()).style(text::danger).size(12)))
+    .spacing(4)
+    .into()
+}
+
+fn optional_shortcut_string(shortcut: Option<GlobalShortcut>) -> String {
+    shortcut.map_or("None".into(), |shortcut| shortcut.to_string())
+}
+
+/// Shows line breaks and tabs as `\n` and `\t` so they can be edited in a single line input
+fn escape_separator(separator: &str) -> String {
+    separator.replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn unescape_separator(input: &str) -> String {
+    input.replace("\\n", "\n").replace("\\t", "\t")
+}
+
+fn parse_number(input: &str) -> Option<u64> {
+    if input.is_empty() {
+        Some(0)
+    } else {
+        input.parse().ok()
+    }
+}
+
 fn shortcut_string(
     Shortcut {
         modifiers,
@@ -0,0 +1,94 @@
+use iced::{
+    widget::{
+        button, center, column, container, horizontal_rule, horizontal_space, row, scrollable,
+        text, Column,
+    },
+    Alignment, Element, Length, Task,
+};
+
+use crate::{app, db::Item};
+
+#[derive(Debug)]
+pub enum State {
+    Loaded { items: Vec<Item> },
+    Loading,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Restore(i32),
+    RestoreAll,
+    EmptyTrash,
+    Loaded(Vec<Item>),
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Task<app::Message> {
+        match message {
+            Message::Restore(id) => Task::done(app::Message::RestoreItems(vec![id])),
+            Message::RestoreAll => match self {
+                State::Loaded { items } if !items.is_empty() => Task::done(
+                    app::Message::RestoreItems(items.iter().map(|item| item.entry.id).collect()),
+                ),
+                _ => Task::none(),
+            },
+            Message::EmptyTrash => Task::done(app::Message::EmptyTrash),
+            Message::Loaded(items) => {
+                *self = State::Loaded { items };
+                Task::none()
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let content: Element<Message> = match self {
+            State::Loaded { items } if items.is_empty() => center(text!("Trash is empty")).into(),
+            State::Loaded { items } => scrollable(
+                Column::from_iter(items.iter().map(|item| {
+                    let deleted_at = item
+                        .entry
+                        .deleted_at
+                        .map(|deleted_at| deleted_at.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    container(
+                        row![
+                            column![
+                                text!("{}", item.entry.data)
+                                    .size(13)
+                                    .wrapping(text::Wrapping::None),
+                                text!("Deleted {deleted_at}").size(11),
+                            ]
+                            .width(Length::Fill),
+                            button(text!("Restore")).on_press(Message::Restore(item.entry.id)),
+                        ]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                    )
+                    .style(container::rounded_box)
+                    .padding(8)
+                    .width(Length::Fill)
+                    .into()
+                }))
+                .spacing(4),
+            )
+            .height(Length::Fill)
+            .into(),
+            State::Loading => center(text!("Loading...")).into(),
+        };
+
+        column![
+            row![
+                text!("Trash").size(30),
+                horizontal_space(),
+                button(text!("Restore all")).on_press(Message::RestoreAll),
+                button(text!("Empty trash")).on_press(Message::EmptyTrash),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            container(horizontal_rule(2)).padding([10, 0]),
+            content,
+        ]
+        .padding(16)
+        .into()
+    }
+}
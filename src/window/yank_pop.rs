@@ -0,0 +1,45 @@
+use iced::{
+    widget::{column, container, text},
+    Element, Length,
+};
+
+use crate::app;
+
+/// Indicator shown while cycling through history with yank pop, it lives as long as the session.
+#[derive(Debug)]
+pub struct State {
+    pub index: u64,
+    pub preview: String,
+    /// Characters of the entry pasted by this session, to be replaced by the next one
+    pub pasted_chars: Option<usize>,
+    /// Incremented on each press so only the last timeout ends the session
+    pub generation: u64,
+}
+
+impl State {
+    pub fn new(index: u64, preview: String) -> State {
+        State {
+            index,
+            preview,
+            pasted_chars: None,
+            generation: 0,
+        }
+    }
+
+    pub fn view(&self) -> Element<app::Message> {
+        container(
+            column![
+                text!("Yank #{}", self.index + 1).size(11),
+                text!("{}", self.preview)
+                    .size(13)
+                    .wrapping(text::Wrapping::None),
+            ]
+            .spacing(2),
+        )
+        .style(container::rounded_box)
+        .padding(8)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}